@@ -0,0 +1,172 @@
+use super::core::UIAutomation;
+use super::core::UIElement;
+use super::core::UIMatcher;
+use super::errors::Result;
+use super::patterns::UIWindowPattern;
+use super::processes::Process;
+use super::processes::ProcessBuilder;
+
+/// An application session which owns a launched or attached process and the `UIAutomation` to access its windows.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use uiautomation::applications::Application;
+/// 
+/// let app = Application::launch("notepad.exe").unwrap().kill_on_drop(true);
+/// let window = app.top_window(5000).unwrap();
+/// println!("{}", window.get_name().unwrap());
+/// app.close().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Application {
+    process: Process,
+    automation: UIAutomation,
+    close_timeout: u32,
+    kill_on_drop: bool
+}
+
+impl Application {
+    /// Launches the application by command line.
+    pub fn launch(command: &str) -> Result<Self> {
+        let process = Process::create(command)?;
+        Self::attach(process)
+    }
+
+    /// Launches the application by the process `builder`.
+    pub fn spawn(builder: &ProcessBuilder) -> Result<Self> {
+        let process = builder.spawn()?;
+        Self::attach(process)
+    }
+
+    /// Attaches to the `process`.
+    pub fn attach(process: Process) -> Result<Self> {
+        let automation = UIAutomation::new()?;
+
+        Ok(Self {
+            process,
+            automation,
+            close_timeout: 5000,
+            kill_on_drop: false
+        })
+    }
+
+    /// Attaches to the running process by the process `id`.
+    pub fn attach_pid(id: u32) -> Result<Self> {
+        let process = Process::from_pid(id)?;
+        Self::attach(process)
+    }
+
+    /// Sets the milliseconds to wait for the application to exit when it is closed. The default timeout is 5000 milliseconds.
+    pub fn close_timeout(mut self, timeout: u32) -> Self {
+        self.close_timeout = timeout;
+        self
+    }
+
+    /// Sets whether to terminate the process when the application is dropped. Default is `false`.
+    /// 
+    /// It keeps a failed test from leaving the application running.
+    pub fn kill_on_drop(mut self, kill_on_drop: bool) -> Self {
+        self.kill_on_drop = kill_on_drop;
+        self
+    }
+
+    /// Retrieves the process of the application.
+    pub fn get_process(&self) -> &Process {
+        &self.process
+    }
+
+    /// Retrieves the `UIAutomation` of the application.
+    pub fn get_automation(&self) -> &UIAutomation {
+        &self.automation
+    }
+
+    /// Waits for the top window of the application, which is the first visible top-level window of the process.
+    /// 
+    /// `timeout` is the milliseconds to wait for.
+    pub fn top_window(&self, timeout: u32) -> Result<UIElement> {
+        self.process.find_main_window(&self.automation, timeout)
+    }
+
+    /// Retrieves all the top-level windows of the application.
+    pub fn windows(&self) -> Result<Vec<UIElement>> {
+        self.process.find_windows(&self.automation)
+    }
+
+    /// Creates a matcher which only matches the elements of the application.
+    pub fn matcher(&self) -> UIMatcher {
+        self.automation.create_matcher().process_id(self.process.get_id() as _)
+    }
+
+    /// Finds the first top-level window of the application which matches the `matcher`.
+    /// 
+    /// The search starts from the desktop with depth `2`, which overrides the root element and the depth of the matcher.
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// use uiautomation::applications::Application;
+    /// 
+    /// let app = Application::launch("notepad.exe").unwrap();
+    /// let window = app.window(app.matcher().classname("Notepad")).unwrap();
+    /// ```
+    pub fn window(&self, matcher: UIMatcher) -> Result<UIElement> {
+        let root = self.automation.get_root_element()?;
+        matcher.from(root)
+            .depth(2)
+            .process_id(self.process.get_id() as _)
+            .find_first()
+    }
+
+    /// Determines whether the process has exited.
+    pub fn is_exited(&self) -> bool {
+        self.process.wait_for(0).is_ok()
+    }
+
+    /// Closes the application.
+    /// 
+    /// The windows are closed by `UIWindowPattern` first. The process is terminated if it does not exit in the close timeout.
+    pub fn close(&self) -> Result<()> {
+        if self.is_exited() {
+            return Ok(());
+        }
+
+        if let Ok(windows) = self.windows() {
+            for window in windows {
+                if let Ok(pattern) = window.get_pattern::<UIWindowPattern>() {
+                    let _ = pattern.close();
+                }
+            }
+        }
+
+        if self.process.wait_for(self.close_timeout).is_ok() {
+            return Ok(());
+        }
+
+        self.process.terminate(1)?;
+        self.process.wait_for(self.close_timeout)
+    }
+}
+
+impl Drop for Application {
+    fn drop(&mut self) {
+        if self.kill_on_drop && !self.is_exited() {
+            let _ = self.process.terminate(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::applications::Application;
+
+    #[test]
+    fn close_notepad() {
+        let app = Application::launch("notepad.exe").unwrap().kill_on_drop(true).close_timeout(3000);
+        let window = app.top_window(5000).unwrap();
+        assert_eq!(window.get_process_id().unwrap() as u32, app.get_process().get_id());
+
+        app.close().unwrap();
+        assert!(app.is_exited());
+    }
+}
@@ -0,0 +1,467 @@
+use std::cell::RefCell;
+use std::thread::sleep;
+use std::time::Duration;
+
+use super::Error;
+use super::Result;
+use super::UIElement;
+use super::errors::ERR_FORMAT;
+use super::hotkeys::lookup_hotkey_key;
+use super::inputs::InputEvent;
+use super::inputs::InputSink;
+use super::inputs::KeyCode;
+use super::inputs::KeyExpression;
+use super::inputs::Keyboard;
+use super::inputs::Mouse;
+use super::inputs::MouseButton;
+use super::inputs::SendInputSink;
+use super::inputs::lookup_key;
+use super::trajectory::Trajectory;
+use super::types::Point;
+
+/// An action of an action chain.
+#[derive(Debug, Clone)]
+enum Action {
+    KeyDown(String),
+    KeyUp(String),
+    MoveTo(Point),
+    MoveToElement(UIElement),
+    Press(MouseButton),
+    Release(MouseButton),
+    Pause(u64),
+    SendKeys(String)
+}
+
+/// A key or a button which is held down by an action chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Held {
+    Key(KeyCode),
+    Button(MouseButton)
+}
+
+impl Held {
+    fn release_event(&self) -> InputEvent {
+        match self {
+            Held::Key(key) => InputEvent::KeyUp(*key),
+            Held::Button(button) => InputEvent::ButtonUp(*button)
+        }
+    }
+}
+
+/// A chain of keyboard and mouse actions which are performed as a single ordered stream of input events.
+/// 
+/// The keys and buttons which are still held down are released when performing fails or the chain is dropped.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use uiautomation::chains::ActionChain;
+/// use uiautomation::inputs::MouseButton;
+/// use uiautomation::inputs::RecordingSink;
+/// use uiautomation::types::Point;
+/// 
+/// let sink = RecordingSink::new();
+/// let chain = ActionChain::with_sink(&sink)
+///     .move_time(0)
+///     .move_to(Point::new(100, 100))
+///     .press(MouseButton::Left)
+///     .key_down("shift")
+///     .move_to(Point::new(100, 300))
+///     .release(MouseButton::Left)
+///     .key_up("shift");
+/// chain.perform().unwrap();
+/// assert_eq!(sink.get_events().len(), 6);
+/// ```
+#[derive(Debug)]
+pub struct ActionChain<S: InputSink = SendInputSink> {
+    actions: Vec<Action>,
+    interval: u64,
+    move_time: u64,
+    trajectory: Option<Trajectory>,
+    held: RefCell<Vec<Held>>,
+    sink: S
+}
+
+impl Default for ActionChain {
+    fn default() -> Self {
+        Self::with_sink(SendInputSink)
+    }
+}
+
+impl ActionChain {
+    /// Creates an empty action chain.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S: InputSink> ActionChain<S> {
+    /// Creates an empty action chain which sends the input events to the `sink`.
+    pub fn with_sink(sink: S) -> Self {
+        Self {
+            actions: Vec::new(),
+            interval: 0,
+            move_time: 500,
+            trajectory: None,
+            held: RefCell::new(Vec::new()),
+            sink
+        }
+    }
+
+    /// Retrieves the sink which receives the input events.
+    pub fn get_sink(&self) -> &S {
+        &self.sink
+    }
+
+    /// Sets the interval time in milliseconds between actions. `0` is default value.
+    pub fn interval(mut self, interval: u64) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the mouse move time in milliseconds. `500` is default value.
+    pub fn move_time(mut self, move_time: u64) -> Self {
+        self.move_time = move_time;
+        self
+    }
+
+    /// Sets the `trajectory` of the cursor movements.
+    pub fn trajectory(mut self, trajectory: Trajectory) -> Self {
+        self.trajectory = Some(trajectory);
+        self
+    }
+
+    /// Presses the `key` down until `key_up()`.
+    /// 
+    /// The `key` is a name used by key expressions or hotkeys, such as `shift`, `ctrl`, `a` or `SC 0x2A`.
+    pub fn key_down(mut self, key: &str) -> Self {
+        self.actions.push(Action::KeyDown(key.to_string()));
+        self
+    }
+
+    /// Releases the `key` pressed by `key_down()`.
+    pub fn key_up(mut self, key: &str) -> Self {
+        self.actions.push(Action::KeyUp(key.to_string()));
+        self
+    }
+
+    /// Moves the cursor to the `pos` position.
+    pub fn move_to(mut self, pos: Point) -> Self {
+        self.actions.push(Action::MoveTo(pos));
+        self
+    }
+
+    /// Moves the cursor to the clickable point of the `element`, which is retrieved when the action is performed.
+    pub fn move_to_element(mut self, element: &UIElement) -> Self {
+        self.actions.push(Action::MoveToElement(element.clone()));
+        self
+    }
+
+    /// Presses the mouse `button` down until `release()`.
+    pub fn press(mut self, button: MouseButton) -> Self {
+        self.actions.push(Action::Press(button));
+        self
+    }
+
+    /// Releases the mouse `button` pressed by `press()`.
+    pub fn release(mut self, button: MouseButton) -> Self {
+        self.actions.push(Action::Release(button));
+        self
+    }
+
+    /// Clicks the mouse `button` at the current position.
+    pub fn click(self, button: MouseButton) -> Self {
+        self.press(button).release(button)
+    }
+
+    /// Waits for `millis` milliseconds.
+    pub fn pause(mut self, millis: u64) -> Self {
+        self.actions.push(Action::Pause(millis));
+        self
+    }
+
+    /// Types the `keys` expression, which is the same as `Keyboard::send_keys()`.
+    pub fn send_keys(mut self, keys: &str) -> Self {
+        self.actions.push(Action::SendKeys(keys.to_string()));
+        self
+    }
+
+    /// Performs the actions in order.
+    /// 
+    /// The keys and the expressions are validated before sending any event.
+    /// If an action fails, all the keys and buttons held by the chain are released.
+    /// Otherwise, the keys and buttons which are not released by the actions stay held until `release_all()` or drop.
+    pub fn perform(&self) -> Result<()> {
+        for action in &self.actions {
+            match action {
+                Action::KeyDown(key) | Action::KeyUp(key) => {
+                    get_key_code(key)?;
+                },
+                Action::SendKeys(keys) => {
+                    KeyExpression::parse(keys)?;
+                },
+                _ => ()
+            }
+        }
+
+        let ret = self.perform_actions();
+        if ret.is_err() {
+            let _ = self.release_all();
+        }
+        ret
+    }
+
+    /// Releases all the keys and buttons which are held by the chain, in reverse order.
+    /// 
+    /// The keys and buttons are still held by the chain if the release fails, so they can be released again.
+    pub fn release_all(&self) -> Result<()> {
+        let events: Vec<InputEvent> = self.held.borrow().iter().rev().map(|held| held.release_event()).collect();
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        self.sink.send(&events)?;
+        self.held.borrow_mut().clear();
+
+        Ok(())
+    }
+
+    /// Retrieves whether the chain holds any key or button down.
+    pub fn is_holding(&self) -> bool {
+        !self.held.borrow().is_empty()
+    }
+
+    fn perform_actions(&self) -> Result<()> {
+        for (i, action) in self.actions.iter().enumerate() {
+            if i > 0 && self.interval > 0 {
+                sleep(Duration::from_millis(self.interval));
+            }
+
+            match action {
+                Action::KeyDown(key) => {
+                    let key = get_key_code(key)?;
+                    self.hold(Held::Key(key), InputEvent::KeyDown(key))?;
+                },
+                Action::KeyUp(key) => self.unhold(Held::Key(get_key_code(key)?))?,
+                Action::MoveTo(pos) => self.create_mouse().move_to(*pos)?,
+                Action::MoveToElement(element) => {
+                    let pos = element.get_click_point()?;
+                    self.create_mouse().move_to(pos)?;
+                },
+                Action::Press(button) => self.hold(Held::Button(*button), InputEvent::ButtonDown(*button))?,
+                Action::Release(button) => self.unhold(Held::Button(*button))?,
+                Action::Pause(millis) => sleep(Duration::from_millis(*millis)),
+                Action::SendKeys(keys) => Keyboard::with_sink(&self.sink).warn_modifiers(false).send_keys(keys)?
+            }
+        }
+
+        Ok(())
+    }
+
+    fn hold(&self, held: Held, event: InputEvent) -> Result<()> {
+        self.sink.send(&[event])?;
+
+        let mut holds = self.held.borrow_mut();
+        if !holds.contains(&held) {
+            holds.push(held);
+        }
+
+        Ok(())
+    }
+
+    fn unhold(&self, held: Held) -> Result<()> {
+        // keeps the key or button held if the release fails, so `release_all()` releases it.
+        self.sink.send(&[held.release_event()])?;
+        self.held.borrow_mut().retain(|h| *h != held);
+
+        Ok(())
+    }
+
+    fn create_mouse(&self) -> Mouse<&S> {
+        let mouse = Mouse::with_sink(&self.sink).interval(0).move_time(self.move_time);
+        match self.trajectory {
+            Some(trajectory) => mouse.trajectory(trajectory),
+            None => mouse
+        }
+    }
+}
+
+impl<S: InputSink> Drop for ActionChain<S> {
+    fn drop(&mut self) {
+        let _ = self.release_all();
+    }
+}
+
+fn get_key_code(key: &str) -> Result<KeyCode> {
+    if let Some((_, code)) = lookup_key(key) {
+        Ok(code)
+    } else if let Some(key) = lookup_hotkey_key(key) {
+        Ok(KeyCode::Virtual(key))
+    } else {
+        Err(Error::new(ERR_FORMAT, &format!("Unknown key `{}`", key)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+    use crate::Error;
+    use crate::Result;
+    use crate::chains::ActionChain;
+    use crate::inputs::InputEvent;
+    use crate::inputs::InputSink;
+    use crate::inputs::KeyCode;
+    use crate::inputs::MouseButton;
+    use crate::inputs::RecordingSink;
+    use crate::layouts::KeyboardLayout;
+    use crate::types::Point;
+
+    /// A sink which fails after sending `limit` batches, and fails the first `release_failures` release batches.
+    struct FailingSink {
+        sink: RecordingSink,
+        limit: Cell<usize>,
+        release_failures: Cell<usize>
+    }
+
+    impl InputSink for FailingSink {
+        fn send(&self, events: &[InputEvent]) -> Result<()> {
+            let is_release = matches!(events[0], InputEvent::KeyUp(_) | InputEvent::ButtonUp(_));
+            if is_release && self.release_failures.get() > 0 {
+                self.release_failures.set(self.release_failures.get() - 1);
+                return Err(Error::from("release failed"));
+            }
+            if self.limit.get() == 0 && !is_release {
+                return Err(Error::from("send failed"));
+            }
+            self.limit.set(self.limit.get().saturating_sub(1));
+            self.sink.send(events)
+        }
+
+        fn get_cursor_pos(&self) -> Result<Point> {
+            self.sink.get_cursor_pos()
+        }
+
+        fn get_screen_size(&self) -> Result<(i32, i32)> {
+            self.sink.get_screen_size()
+        }
+
+        fn get_keyboard_layout(&self) -> KeyboardLayout {
+            self.sink.get_keyboard_layout()
+        }
+
+        fn is_key_toggled(&self, key: VIRTUAL_KEY) -> bool {
+            self.sink.is_key_toggled(key)
+        }
+    }
+
+    #[test]
+    fn test_chain_order() {
+        let sink = RecordingSink::new();
+        let chain = ActionChain::with_sink(&sink)
+            .move_time(0)
+            .key_down("shift")
+            .move_to(Point::new(10, 20))
+            .click(MouseButton::Left)
+            .key_up("shift")
+            .key_down("ctrl")
+            .press(MouseButton::Left)
+            .move_to(Point::new(300, 400))
+            .release(MouseButton::Left)
+            .key_up("ctrl")
+            .send_keys("a");
+        chain.perform().unwrap();
+        assert!(!chain.is_holding());
+
+        assert_eq!(
+            sink.get_events(),
+            vec![
+                InputEvent::KeyDown(KeyCode::Virtual(VK_SHIFT)),
+                InputEvent::Move(Point::new(10, 20)),
+                InputEvent::ButtonDown(MouseButton::Left),
+                InputEvent::ButtonUp(MouseButton::Left),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_SHIFT)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_CONTROL)),
+                InputEvent::ButtonDown(MouseButton::Left),
+                InputEvent::Move(Point::new(300, 400)),
+                InputEvent::ButtonUp(MouseButton::Left),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_CONTROL)),
+                InputEvent::UnicodeDown('a' as u16),
+                InputEvent::UnicodeUp('a' as u16)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chain_release_on_drop() {
+        let sink = RecordingSink::new();
+        {
+            let chain = ActionChain::with_sink(&sink).key_down("ctrl").press(MouseButton::Right);
+            chain.perform().unwrap();
+            assert!(chain.is_holding());
+            sink.clear();
+        }
+
+        assert_eq!(sink.get_events(), vec![
+            InputEvent::ButtonUp(MouseButton::Right),
+            InputEvent::KeyUp(KeyCode::Virtual(VK_CONTROL))
+        ]);
+    }
+
+    #[test]
+    fn test_chain_release_on_error() {
+        let sink = FailingSink {
+            sink: RecordingSink::new(),
+            limit: Cell::new(2),
+            release_failures: Cell::new(0)
+        };
+        let chain = ActionChain::with_sink(&sink).move_time(0).key_down("shift").press(MouseButton::Left).move_to(Point::new(5, 5));
+        assert!(chain.perform().is_err());
+        assert!(!chain.is_holding());
+        assert_eq!(sink.sink.get_events(), vec![
+            InputEvent::KeyDown(KeyCode::Virtual(VK_SHIFT)),
+            InputEvent::ButtonDown(MouseButton::Left),
+            InputEvent::ButtonUp(MouseButton::Left),
+            InputEvent::KeyUp(KeyCode::Virtual(VK_SHIFT))
+        ]);
+    }
+
+    #[test]
+    fn test_chain_release_failure() {
+        let sink = FailingSink {
+            sink: RecordingSink::new(),
+            limit: Cell::new(usize::MAX),
+            release_failures: Cell::new(1)
+        };
+        let chain = ActionChain::with_sink(&sink).key_down("shift").press(MouseButton::Left).release(MouseButton::Left);
+        assert!(chain.perform().is_err());
+        assert!(!chain.is_holding());
+        assert_eq!(sink.sink.get_events(), vec![
+            InputEvent::KeyDown(KeyCode::Virtual(VK_SHIFT)),
+            InputEvent::ButtonDown(MouseButton::Left),
+            InputEvent::ButtonUp(MouseButton::Left),
+            InputEvent::KeyUp(KeyCode::Virtual(VK_SHIFT))
+        ]);
+
+        sink.sink.clear();
+        sink.release_failures.set(2);
+        let chain = ActionChain::with_sink(&sink).key_down("ctrl").key_up("ctrl");
+        assert!(chain.perform().is_err());
+        assert!(chain.is_holding());
+        drop(chain);
+        assert_eq!(sink.sink.get_events(), vec![
+            InputEvent::KeyDown(KeyCode::Virtual(VK_CONTROL)),
+            InputEvent::KeyUp(KeyCode::Virtual(VK_CONTROL))
+        ]);
+    }
+
+    #[test]
+    fn test_chain_validation() {
+        let sink = RecordingSink::new();
+        assert!(ActionChain::with_sink(&sink).key_down("shift").key_down("shoft").perform().is_err());
+        assert!(ActionChain::with_sink(&sink).key_down("shift").send_keys("{Entr}").perform().is_err());
+        assert!(sink.get_events().is_empty());
+    }
+}
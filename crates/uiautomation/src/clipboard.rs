@@ -0,0 +1,320 @@
+use std::thread::sleep;
+use std::time::Duration;
+
+use windows::Win32::Foundation::HANDLE;
+use windows::Win32::Foundation::HWND;
+use windows::Win32::System::DataExchange::CloseClipboard;
+use windows::Win32::System::DataExchange::EmptyClipboard;
+use windows::Win32::System::DataExchange::EnumClipboardFormats;
+use windows::Win32::System::DataExchange::GetClipboardData;
+use windows::Win32::System::DataExchange::IsClipboardFormatAvailable;
+use windows::Win32::System::DataExchange::OpenClipboard;
+use windows::Win32::System::DataExchange::RegisterClipboardFormatW;
+use windows::Win32::System::DataExchange::SetClipboardData;
+use windows::Win32::System::Memory::GMEM_MOVEABLE;
+use windows::Win32::System::Memory::GlobalAlloc;
+use windows::Win32::System::Memory::GlobalFree;
+use windows::Win32::System::Memory::GlobalLock;
+use windows::Win32::System::Memory::GlobalSize;
+use windows::Win32::System::Memory::GlobalUnlock;
+use windows::core::PCWSTR;
+
+use super::Error;
+use super::Result;
+use super::errors::ERR_FORMAT;
+use super::errors::ERR_NOTFOUND;
+
+/// The standard `CF_UNICODETEXT` clipboard format.
+const CF_UNICODETEXT: u32 = 13;
+
+/// The clipboard formats whose data are not global memory, which can not be saved and restored.
+const CF_BITMAP: u32 = 2;
+const CF_METAFILEPICT: u32 = 3;
+const CF_PALETTE: u32 = 9;
+const CF_ENHMETAFILE: u32 = 14;
+const CF_OWNERDISPLAY: u32 = 0x80;
+const CF_DSPBITMAP: u32 = 0x82;
+const CF_DSPMETAFILEPICT: u32 = 0x83;
+const CF_DSPENHMETAFILE: u32 = 0x8E;
+
+/// The text formats which are supported by the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardFormat {
+    /// The unicode text format, `CF_UNICODETEXT`.
+    UnicodeText,
+    /// The `HTML Format` which is used by browsers and office applications.
+    Html
+}
+
+impl ClipboardFormat {
+    fn get_id(&self) -> Result<u32> {
+        match self {
+            ClipboardFormat::UnicodeText => Ok(CF_UNICODETEXT),
+            ClipboardFormat::Html => {
+                let name: Vec<u16> = "HTML Format\0".encode_utf16().collect();
+                let id = unsafe { RegisterClipboardFormatW(PCWSTR(name.as_ptr())) };
+                if id == 0 {
+                    Err(Error::last_os_error())
+                } else {
+                    Ok(id)
+                }
+            }
+        }
+    }
+}
+
+/// Reads and writes the system clipboard.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use uiautomation::clipboard::Clipboard;
+/// 
+/// let _guard = Clipboard::save().unwrap();
+/// Clipboard::set_text("Hello, 世界").unwrap();
+/// assert_eq!(Clipboard::get_text().unwrap(), "Hello, 世界");
+/// ```
+#[derive(Debug)]
+pub struct Clipboard;
+
+impl Clipboard {
+    /// Determines whether the clipboard has data of the `format`.
+    pub fn is_available(format: ClipboardFormat) -> bool {
+        format.get_id().is_ok_and(|id| unsafe { IsClipboardFormatAvailable(id) }.as_bool())
+    }
+
+    /// Retrieves the unicode text of the clipboard.
+    pub fn get_text() -> Result<String> {
+        let _opened = OpenedClipboard::open()?;
+        let data = get_data(CF_UNICODETEXT)?;
+        let text: Vec<u16> = data.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).take_while(|c| *c != 0).collect();
+        Ok(String::from_utf16_lossy(&text))
+    }
+
+    /// Replaces the clipboard contents with the unicode `text`.
+    pub fn set_text(text: &str) -> Result<()> {
+        let opened = OpenedClipboard::open()?;
+        opened.empty()?;
+        set_data(CF_UNICODETEXT, &encode_text(text))
+    }
+
+    /// Retrieves the HTML fragment of the clipboard.
+    pub fn get_html() -> Result<String> {
+        let format = ClipboardFormat::Html.get_id()?;
+        let _opened = OpenedClipboard::open()?;
+        let data = get_data(format)?;
+        let end = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+        parse_html(&String::from_utf8_lossy(&data[..end]))
+    }
+
+    /// Replaces the clipboard contents with the `html` fragment, and the plain `text` for the applications which do not accept HTML.
+    pub fn set_html(html: &str, text: &str) -> Result<()> {
+        let format = ClipboardFormat::Html.get_id()?;
+        let opened = OpenedClipboard::open()?;
+        opened.empty()?;
+
+        let mut data = format_html(html).into_bytes();
+        data.push(0);
+        set_data(format, &data)?;
+        set_data(CF_UNICODETEXT, &encode_text(text))
+    }
+
+    /// Saves the current clipboard contents, which are restored when the returned guard is dropped.
+    /// 
+    /// The formats which are not stored in global memory, such as bitmaps and metafiles, are not saved.
+    pub fn save() -> Result<ClipboardGuard> {
+        let _opened = OpenedClipboard::open()?;
+
+        let mut contents: Vec<(u32, Vec<u8>)> = Vec::new();
+        let mut format = unsafe { EnumClipboardFormats(0) };
+        while format != 0 {
+            if is_memory_format(format) {
+                if let Ok(data) = get_data(format) {
+                    contents.push((format, data));
+                }
+            }
+            format = unsafe { EnumClipboardFormats(format) };
+        }
+
+        Ok(ClipboardGuard {
+            contents: Some(contents)
+        })
+    }
+}
+
+/// A guard which restores the saved clipboard contents when it is dropped.
+#[derive(Debug)]
+pub struct ClipboardGuard {
+    contents: Option<Vec<(u32, Vec<u8>)>>
+}
+
+impl ClipboardGuard {
+    /// Restores the saved clipboard contents now.
+    /// 
+    /// Every saved format is restored even if some of them fail, and the first error is returned.
+    pub fn restore(mut self) -> Result<()> {
+        self.restore_contents()
+    }
+
+    fn restore_contents(&mut self) -> Result<()> {
+        if let Some(contents) = self.contents.take() {
+            let opened = OpenedClipboard::open()?;
+            opened.empty()?;
+            // restores as many formats as possible, and reports the first failure.
+            let mut ret = Ok(());
+            for (format, data) in contents {
+                let result = set_data(format, &data);
+                if ret.is_ok() {
+                    ret = result;
+                }
+            }
+            return ret;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for ClipboardGuard {
+    fn drop(&mut self) {
+        let _ = self.restore_contents();
+    }
+}
+
+/// Keeps the clipboard open, and closes it when dropped.
+struct OpenedClipboard;
+
+impl OpenedClipboard {
+    /// Opens the clipboard, retrying for a while if another window has opened it.
+    fn open() -> Result<Self> {
+        for _ in 0..10 {
+            if unsafe { OpenClipboard(HWND::default()) }.as_bool() {
+                return Ok(Self);
+            }
+            sleep(Duration::from_millis(20));
+        }
+
+        Err(Error::last_os_error())
+    }
+
+    fn empty(&self) -> Result<()> {
+        if unsafe { EmptyClipboard() }.as_bool() {
+            Ok(())
+        } else {
+            Err(Error::last_os_error())
+        }
+    }
+}
+
+impl Drop for OpenedClipboard {
+    fn drop(&mut self) {
+        unsafe { CloseClipboard() };
+    }
+}
+
+fn is_memory_format(format: u32) -> bool {
+    !matches!(format, CF_BITMAP | CF_METAFILEPICT | CF_PALETTE | CF_ENHMETAFILE | CF_OWNERDISPLAY | CF_DSPBITMAP | CF_DSPMETAFILEPICT | CF_DSPENHMETAFILE | 0x200..=0x3FF)
+}
+
+fn get_data(format: u32) -> Result<Vec<u8>> {
+    if !unsafe { IsClipboardFormatAvailable(format) }.as_bool() {
+        return Err(Error::new(ERR_NOTFOUND, "The clipboard format is not available"));
+    }
+
+    let handle = unsafe { GetClipboardData(format)? };
+    let size = unsafe { GlobalSize(handle.0) };
+    let ptr = unsafe { GlobalLock(handle.0) } as *const u8;
+    if ptr.is_null() {
+        return Err(Error::last_os_error());
+    }
+
+    let data = unsafe { std::slice::from_raw_parts(ptr, size) }.to_vec();
+    unsafe { GlobalUnlock(handle.0) };
+
+    Ok(data)
+}
+
+fn set_data(format: u32, data: &[u8]) -> Result<()> {
+    let memory = unsafe { GlobalAlloc(GMEM_MOVEABLE, data.len().max(1)) };
+    if memory == 0 {
+        return Err(Error::last_os_error());
+    }
+
+    unsafe {
+        let ptr = GlobalLock(memory) as *mut u8;
+        if ptr.is_null() {
+            GlobalFree(memory);
+            return Err(Error::last_os_error());
+        }
+        std::ptr::copy_nonoverlapping(data.as_ptr(), ptr, data.len());
+        GlobalUnlock(memory);
+
+        // the system owns the memory after `SetClipboardData()` succeeds.
+        if let Err(e) = SetClipboardData(format, HANDLE(memory)) {
+            GlobalFree(memory);
+            return Err(e.into());
+        }
+    }
+
+    Ok(())
+}
+
+fn encode_text(text: &str) -> Vec<u8> {
+    text.encode_utf16().chain(std::iter::once(0)).flat_map(|c| c.to_le_bytes()).collect()
+}
+
+const HTML_START: &str = "<html><body>\r\n<!--StartFragment-->";
+const HTML_END: &str = "<!--EndFragment-->\r\n</body></html>";
+
+/// Wraps the `html` fragment in the `HTML Format` clipboard description, whose offsets are counted in UTF-8 bytes.
+pub fn format_html(html: &str) -> String {
+    let header = |start_html: usize, end_html: usize, start_fragment: usize, end_fragment: usize| {
+        format!("Version:0.9\r\nStartHTML:{:010}\r\nEndHTML:{:010}\r\nStartFragment:{:010}\r\nEndFragment:{:010}\r\n",
+            start_html, end_html, start_fragment, end_fragment)
+    };
+
+    let start_html = header(0, 0, 0, 0).len();
+    let start_fragment = start_html + HTML_START.len();
+    let end_fragment = start_fragment + html.len();
+    let end_html = end_fragment + HTML_END.len();
+
+    format!("{}{}{}{}", header(start_html, end_html, start_fragment, end_fragment), HTML_START, html, HTML_END)
+}
+
+/// Extracts the fragment from the `HTML Format` clipboard description.
+pub fn parse_html(data: &str) -> Result<String> {
+    let offset = |name: &str| -> Option<usize> {
+        data.lines().find_map(|line| line.strip_prefix(name)).and_then(|v| v.trim().parse::<usize>().ok())
+    };
+
+    match (offset("StartFragment:"), offset("EndFragment:")) {
+        (Some(start), Some(end)) if start <= end && end <= data.len() && data.is_char_boundary(start) && data.is_char_boundary(end) => {
+            Ok(data[start..end].to_string())
+        },
+        _ => Err(Error::new(ERR_FORMAT, "Invalid HTML clipboard format"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::clipboard::format_html;
+    use crate::clipboard::parse_html;
+
+    #[test]
+    fn test_html_format() {
+        let data = format_html("<b>Grüße</b>");
+        assert!(data.starts_with("Version:0.9\r\nStartHTML:0000000105\r\n"));
+        assert_eq!(parse_html(&data).unwrap(), "<b>Grüße</b>");
+
+        let start = data.find("<html>").unwrap();
+        assert_eq!(start, 105);
+        assert!(data.ends_with("</body></html>"));
+        assert_eq!(data.find("EndHTML:").map(|i| &data[i + 8..i + 18]), Some(format!("{:010}", data.len()).as_str()));
+    }
+
+    #[test]
+    fn test_html_parse_error() {
+        assert!(parse_html("Version:0.9\r\nStartFragment:10\r\n").is_err());
+        assert!(parse_html("StartFragment:50\r\nEndFragment:10\r\n").is_err());
+    }
+}
@@ -7,6 +7,7 @@ use std::time::Duration;
 use chrono::Local;
 use windows::Win32::Foundation::BOOL;
 use windows::Win32::Foundation::BSTR;
+use windows::Win32::Foundation::HWND;
 use windows::Win32::System::Com::CLSCTX_ALL;
 use windows::Win32::System::Com::COINIT_MULTITHREADED;
 use windows::Win32::System::Com::CoCreateInstance;
@@ -14,6 +15,9 @@ use windows::Win32::System::Com::CoInitializeEx;
 use windows::Win32::System::Com::VARIANT;
 use windows::Win32::UI::Accessibility::CUIAutomation;
 use windows::Win32::UI::Accessibility::IUIAutomation;
+use windows::Win32::UI::Accessibility::IUIAutomation3;
+use windows::Win32::UI::Accessibility::IUIAutomation5;
+use windows::Win32::UI::Accessibility::IUIAutomation6;
 use windows::Win32::UI::Accessibility::IUIAutomationAndCondition;
 use windows::Win32::UI::Accessibility::IUIAutomationBoolCondition;
 use windows::Win32::UI::Accessibility::IUIAutomationCondition;
@@ -26,6 +30,7 @@ use windows::Win32::UI::Accessibility::IUIAutomationPropertyCondition;
 use windows::Win32::UI::Accessibility::IUIAutomationTreeWalker;
 use windows::Win32::UI::Accessibility::OrientationType;
 use windows::Win32::UI::Accessibility::PropertyConditionFlags;
+use windows::Win32::UI::Accessibility::TextEditChangeType;
 use windows::Win32::UI::Accessibility::TreeScope;
 use windows::core::InParam;
 use windows::core::Interface;
@@ -34,16 +39,27 @@ use crate::filters::FnFilter;
 use crate::inputs::Mouse;
 use crate::variants::SafeArray;
 
+use super::clipboard::Clipboard;
+use super::events::UIActiveTextPositionChangedEventHandler;
+use super::events::UINotificationEventHandler;
+use super::events::UITextEditTextChangedEventHandler;
 use super::filters::ClassNameFilter;
 use super::filters::MatcherFilter;
 use super::filters::ControlTypeFilter;
 use super::filters::NameFilter;
+use super::filters::ProcessIdFilter;
 use super::errors::ERR_NOTFOUND;
 use super::errors::ERR_TIMEOUT;
+use super::errors::ERR_TYPE;
 use super::errors::Error;
 use super::errors::Result;
+use super::inputs::InputMode;
+use super::inputs::InputSink;
 use super::inputs::Keyboard;
+use super::inputs::MessageSink;
+use super::inputs::SendInputSink;
 use super::patterns::UIPattern;
+use super::screens::set_dpi_awareness;
 use super::types::Handle;
 use super::types::Rect;
 use super::types::Point;
@@ -59,7 +75,12 @@ pub struct UIAutomation {
 
 impl UIAutomation {
     /// Creates a uiautomation client instance. 
+    /// 
+    /// The process is made per-monitor DPI aware if its DPI awareness is not set yet, 
+    /// so the element rects and the mouse coordinates are both in physical pixels.
     pub fn new() -> Result<UIAutomation> {
+        let _ = set_dpi_awareness();
+
         let automation: IUIAutomation = unsafe {
             CoInitializeEx(null_mut(), COINIT_MULTITHREADED)?;
             CoCreateInstance(&CUIAutomation, None, CLSCTX_ALL)?
@@ -249,6 +270,97 @@ impl UIAutomation {
         };
         Ok(condition.into())
     }
+
+    /// Registers a method that handles notification events raised by `UiaRaiseNotificationEvent`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use uiautomation::UIAutomation;
+    /// use uiautomation::UIElement;
+    /// use uiautomation::events::CustomNotificationEventHandler;
+    /// use uiautomation::events::Notification;
+    /// use uiautomation::events::UINotificationEventHandler;
+    /// use windows::Win32::UI::Accessibility::TreeScope_Subtree;
+    /// 
+    /// struct MyHandler;
+    /// 
+    /// impl CustomNotificationEventHandler for MyHandler {
+    ///     fn handle(&self, sender: &UIElement, notification: &Notification) -> uiautomation::Result<()> {
+    ///         println!("{} -> {}", sender, notification);
+    ///         Ok(())
+    ///     }
+    /// }
+    /// 
+    /// let automation = UIAutomation::new().unwrap();
+    /// let root = automation.get_root_element().unwrap();
+    /// let handler: Box<dyn CustomNotificationEventHandler> = Box::new(MyHandler);
+    /// let handler = UINotificationEventHandler::from(handler);
+    /// automation.add_notification_event_handler(&root, TreeScope_Subtree, &handler).unwrap();
+    /// automation.remove_notification_event_handler(&root, &handler).unwrap();
+    /// ```
+    pub fn add_notification_event_handler(&self, element: &UIElement, scope: TreeScope, handler: &UINotificationEventHandler) -> Result<()> {
+        let automation5: IUIAutomation5 = self.automation.cast()?;
+        unsafe {
+            automation5.AddNotificationEventHandler(element.as_ref(), scope, InParam::null(), handler.as_ref())?
+        };
+        Ok(())
+    }
+
+    /// Removes a notification event handler.
+    pub fn remove_notification_event_handler(&self, element: &UIElement, handler: &UINotificationEventHandler) -> Result<()> {
+        let automation5: IUIAutomation5 = self.automation.cast()?;
+        unsafe {
+            automation5.RemoveNotificationEventHandler(element.as_ref(), handler.as_ref())?
+        };
+        Ok(())
+    }
+
+    /// Registers a method that handles programmatic text-edit events.
+    /// 
+    /// `change_type` specifies the type of text-edit change to listen for.
+    pub fn add_text_edit_text_changed_event_handler(&self, element: &UIElement, scope: TreeScope, change_type: TextEditChangeType, handler: &UITextEditTextChangedEventHandler) -> Result<()> {
+        let automation3: IUIAutomation3 = self.automation.cast()?;
+        unsafe {
+            automation3.AddTextEditTextChangedEventHandler(element.as_ref(), scope, change_type, InParam::null(), handler.as_ref())?
+        };
+        Ok(())
+    }
+
+    /// Removes a text-edit text-changed event handler.
+    pub fn remove_text_edit_text_changed_event_handler(&self, element: &UIElement, handler: &UITextEditTextChangedEventHandler) -> Result<()> {
+        let automation3: IUIAutomation3 = self.automation.cast()?;
+        unsafe {
+            automation3.RemoveTextEditTextChangedEventHandler(element.as_ref(), handler.as_ref())?
+        };
+        Ok(())
+    }
+
+    /// Registers a method that handles active text position changed events.
+    pub fn add_active_text_position_changed_event_handler(&self, element: &UIElement, scope: TreeScope, handler: &UIActiveTextPositionChangedEventHandler) -> Result<()> {
+        let automation6: IUIAutomation6 = self.automation.cast()?;
+        unsafe {
+            automation6.AddActiveTextPositionChangedEventHandler(element.as_ref(), scope, InParam::null(), handler.as_ref())?
+        };
+        Ok(())
+    }
+
+    /// Removes an active text position changed event handler.
+    pub fn remove_active_text_position_changed_event_handler(&self, element: &UIElement, handler: &UIActiveTextPositionChangedEventHandler) -> Result<()> {
+        let automation6: IUIAutomation6 = self.automation.cast()?;
+        unsafe {
+            automation6.RemoveActiveTextPositionChangedEventHandler(element.as_ref(), handler.as_ref())?
+        };
+        Ok(())
+    }
+
+    /// Removes all registered event handlers.
+    pub fn remove_all_event_handlers(&self) -> Result<()> {
+        unsafe {
+            self.automation.RemoveAllEventHandlers()?
+        };
+        Ok(())
+    }
 }
 
 impl From<IUIAutomation> for UIAutomation {
@@ -276,7 +388,8 @@ impl AsRef<IUIAutomation> for UIAutomation {
 /// Exposes methods and properties for a UI Automation element, which represents a UI item.
 #[derive(Clone)]
 pub struct UIElement {
-    element: IUIAutomationElement
+    element: IUIAutomationElement,
+    input_mode: InputMode
 }
 
 impl UIElement {
@@ -655,6 +768,8 @@ impl UIElement {
     /// 
     /// `{}()` can be quoted by `{}`. For example: `{{}Hi,{(}rust!{)}{}}` types `{Hi,(rust)}`.
     /// 
+    /// Special keys can be repeated by a count. For example: `{TAB 5}`, `{DOWN*15}`.
+    /// 
     /// `interval` is the milliseconds between keys. `0` is the default value.
     /// 
     /// # Examples
@@ -667,49 +782,202 @@ impl UIElement {
     /// root.send_keys("{Win}D", 0).unwrap();
     /// ```
     pub fn send_keys(&self, keys: &str, interval: u64) -> Result<()> {
+        self.send_keys_with_mode(keys, interval, self.input_mode)
+    }
+
+    /// Simulates typing `keys` on the element in the input `mode`, which overrides the input mode of the element.
+    /// 
+    /// `InputMode::Background` posts the keys to the native window of the element without setting focus.
+    pub fn send_keys_with_mode(&self, keys: &str, interval: u64, mode: InputMode) -> Result<()> {
+        match mode {
+            InputMode::Foreground => {
+                self.set_focus()?;
+
+                let kb = Keyboard::new();
+                kb.interval(interval).send_keys(keys)
+            },
+            InputMode::Background => {
+                let kb = Keyboard::with_sink(self.create_message_sink()?);
+                kb.interval(interval).send_keys(keys)
+            }
+        }
+    }
+
+    /// Simulates typing the `text` literally on the element, without parsing special keys and groups.
+    /// 
+    /// `interval` is the milliseconds between keys. The input mode of the element is used.
+    pub fn type_text(&self, text: &str, interval: u64) -> Result<()> {
+        match self.input_mode {
+            InputMode::Foreground => {
+                self.set_focus()?;
+
+                let kb = Keyboard::new();
+                kb.interval(interval).type_text(text)
+            },
+            InputMode::Background => {
+                let kb = Keyboard::with_sink(self.create_message_sink()?);
+                kb.interval(interval).type_text(text)
+            }
+        }
+    }
+
+    /// Sets the input `mode` which is used by the keys, hotkeys and clicks on the element. `InputMode::Foreground` is default value.
+    /// 
+    /// `paste_text()` does not support `InputMode::Background`, because the posted `Ctrl+V` does not paste in most applications.
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// use uiautomation::core::UIAutomation;
+    /// use uiautomation::inputs::InputMode;
+    /// 
+    /// let automation = UIAutomation::new().unwrap();
+    /// let edit = automation.create_matcher().classname("Edit").timeout(1000).find_first().unwrap();
+    /// let edit = edit.with_input_mode(InputMode::Background);
+    /// edit.send_keys("typed in the background", 0).unwrap();
+    /// ```
+    pub fn with_input_mode(mut self, mode: InputMode) -> Self {
+        self.input_mode = mode;
+        self
+    }
+
+    /// Retrieves the input mode of the element.
+    pub fn get_input_mode(&self) -> InputMode {
+        self.input_mode
+    }
+
+    /// Simulates pressing the `hotkey` in menu notation on the element, such as `Ctrl+S` or `Ctrl+K, Ctrl+C`.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use uiautomation::core::UIAutomation;
+    /// 
+    /// let automation = UIAutomation::new().unwrap();
+    /// let root = automation.get_root_element().unwrap();
+    /// root.press_hotkey("Win+D").unwrap();
+    /// ```
+    pub fn press_hotkey(&self, hotkey: &str) -> Result<()> {
+        match self.input_mode {
+            InputMode::Foreground => {
+                self.set_focus()?;
+
+                Keyboard::new().press_hotkey(hotkey)
+            },
+            InputMode::Background => {
+                let kb = Keyboard::with_sink(self.create_message_sink()?);
+                kb.press_hotkey(hotkey)
+            }
+        }
+    }
+
+    /// Pastes the `text` to the element by the clipboard and `Ctrl+V`, which is faster than typing long or complex text.
+    /// 
+    /// The clipboard contents are restored 100 milliseconds after pasting. A slow application may read the clipboard after it is restored
+    /// and paste the old contents, so use `paste_text_with_delay()` to wait longer.
+    pub fn paste_text(&self, text: &str) -> Result<()> {
+        self.paste_text_with_delay(text, 100)
+    }
+
+    /// Pastes the `text` to the element by the clipboard and `Ctrl+V`, and restores the clipboard contents after `delay` milliseconds.
+    /// 
+    /// The application must read the clipboard in the delay, otherwise it pastes the restored contents.
+    /// 
+    /// It fails in `InputMode::Background`.
+    pub fn paste_text_with_delay(&self, text: &str, delay: u64) -> Result<()> {
+        if self.input_mode == InputMode::Background {
+            return Err(Error::new(ERR_TYPE, "paste_text is not supported in the background input mode"));
+        }
+
         self.set_focus()?;
-        
-        let kb = Keyboard::new();
-        kb.interval(interval).send_keys(keys)
+
+        let guard = Clipboard::save()?;
+        Clipboard::set_text(text)?;
+        Keyboard::new().send_keys("{ctrl}v")?;
+
+        // waits for the application to read the clipboard before restoring it.
+        sleep(Duration::from_millis(delay));
+        guard.restore()
     }
 
     /// Simulates mouse left click event on the element.
     pub fn click(&self) -> Result<()> {
-        self.try_focus();
+        self.click_with_mode(self.input_mode)
+    }
 
-        let point = self.get_click_point()?;
-        let mouse = Mouse::default();
-        mouse.click(point)
+    /// Simulates mouse left click event on the element in the input `mode`, which overrides the input mode of the element.
+    /// 
+    /// `InputMode::Background` posts the click to the native window of the element in client coordinates, without moving the cursor.
+    pub fn click_with_mode(&self, mode: InputMode) -> Result<()> {
+        self.with_mouse(mode, |mouse, point| mouse.click(point))
     }
 
     /// Simulates mouse left click event with holdkeys on the element.
     /// 
     /// The holdkey is quoted by `{}`, for example: `{Ctrl}`, `{Ctrl}{Shift}`.
     pub fn hold_click(&self, holdkeys: &str) -> Result<()> {
-        let point = self.get_click_point()?;
-        let mouse = Mouse::default().holdkeys(holdkeys);
-        mouse.click(point)
+        self.with_mouse(self.input_mode, |mouse, point| mouse.holdkeys(holdkeys).click(point))
     }
 
     /// Simulates mouse double click event on the element.
     pub fn double_click(&self) -> Result<()> {
-        self.try_focus();
-        
-        let point = self.get_click_point()?;
-        let mouse = Mouse::default();
-        mouse.double_click(point)
+        self.with_mouse(self.input_mode, |mouse, point| mouse.double_click(point))
     }
 
     /// Simulates mouse right click event on the element.
     pub fn right_click(&self) -> Result<()> {
-        self.try_focus();
+        self.with_mouse(self.input_mode, |mouse, point| mouse.right_click(point))
+    }
 
-        let point = self.get_click_point()?;
-        let mouse = Mouse::default();
-        mouse.right_click(point)
+    /// Simulates mouse middle click event on the element.
+    pub fn middle_click(&self) -> Result<()> {
+        self.with_mouse(self.input_mode, |mouse, point| mouse.middle_click(point))
     }
 
-    fn get_click_point(&self) -> Result<Point> {
+    /// Drags the element and drops it on the `target` element.
+    /// 
+    /// The input mode of the element is used. The drag and drop which depends on mouse capture or OLE does not work in `InputMode::Background`.
+    pub fn drag_to(&self, target: &UIElement) -> Result<()> {
+        let to = target.get_click_point()?;
+        self.with_mouse(self.input_mode, |mouse, from| mouse.drag(from, to))
+    }
+
+    /// Rotates the mouse wheel by `ticks` notches over the element.
+    /// 
+    /// A positive value scrolls up and a negative value scrolls down. The input mode of the element is used.
+    pub fn scroll_wheel(&self, ticks: i32) -> Result<()> {
+        self.with_mouse(self.input_mode, |mouse, point| mouse.scroll_wheel(point, ticks))
+    }
+
+    /// Runs `f` with a mouse of the input `mode` and the click point of the element.
+    fn with_mouse<F: FnOnce(Mouse<&dyn InputSink>, Point) -> Result<()>>(&self, mode: InputMode, f: F) -> Result<()> {
+        match mode {
+            InputMode::Foreground => {
+                self.try_focus();
+
+                let point = self.get_click_point()?;
+                let sink = SendInputSink;
+                f(Mouse::with_sink(&sink as &dyn InputSink), point)
+            },
+            InputMode::Background => {
+                let point = self.get_click_point()?;
+                let sink = self.create_message_sink()?;
+                f(Mouse::with_sink(&sink as &dyn InputSink).interval(0).move_time(0), point)
+            }
+        }
+    }
+
+    fn create_message_sink(&self) -> Result<MessageSink> {
+        let handle = self.get_native_window_handle()?;
+        let hwnd: HWND = handle.into();
+        if hwnd.0 == 0 {
+            Err(Error::new(ERR_NOTFOUND, "The element has no native window handle"))
+        } else {
+            Ok(MessageSink::new(handle))
+        }
+    }
+
+    pub(crate) fn get_click_point(&self) -> Result<Point> {
         if let Ok(Some(point)) = self.get_clickable_point() {
             Ok(point)
         } else {
@@ -723,7 +991,8 @@ impl UIElement {
 impl From<IUIAutomationElement> for UIElement {
     fn from(element: IUIAutomationElement) -> Self {
         UIElement {
-            element
+            element,
+            input_mode: InputMode::default()
         }
     }
 }
@@ -1023,6 +1292,14 @@ impl UIMatcher {
         self.filter(Box::new(condition))
     }
 
+    /// Filters by the id of the process which owns the element.
+    pub fn process_id(self, process_id: i32) -> Self {
+        let condition = ProcessIdFilter {
+            process_id
+        };
+        self.filter(Box::new(condition))
+    }
+
     /// Clears all filters.
     pub fn reset(mut self) -> Self {
         // self.condition = None;
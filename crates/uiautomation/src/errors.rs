@@ -1,7 +1,9 @@
 use std::fmt::Display;
 
+use windows::Win32::Foundation::E_FAIL;
 use windows::Win32::Foundation::GetLastError;
 use windows::core::HRESULT;
+use windows::core::HSTRING;
 
 /// Error caused by unknown reason.
 pub const ERR_NONE: i32 = 0;
@@ -17,6 +19,8 @@ pub const ERR_TYPE: i32 = 4;
 pub const ERR_NULL_PTR:  i32 = 5;
 /// Error format.
 pub const ERR_FORMAT: i32 = 6;
+/// Error occurs when the waited object is abandoned.
+pub const ERR_ABANDONED: i32 = 7;
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Error {
@@ -87,6 +91,13 @@ impl From<HRESULT> for Error {
     }
 }
 
+impl From<Error> for windows::core::Error {
+    fn from(e: Error) -> Self {
+        let code = e.result().unwrap_or(E_FAIL);
+        windows::core::Error::new(code, HSTRING::from(e.message()))
+    }
+}
+
 impl From<String> for Error {
     fn from(message: String) -> Self {
         Error {
@@ -0,0 +1,349 @@
+use std::fmt::Debug;
+use std::fmt::Display;
+
+use windows::Win32::Foundation::BSTR;
+use windows::Win32::System::Com::SAFEARRAY;
+use windows::Win32::UI::Accessibility::IUIAutomationActiveTextPositionChangedEventHandler;
+use windows::Win32::UI::Accessibility::IUIAutomationActiveTextPositionChangedEventHandler_Impl;
+use windows::Win32::UI::Accessibility::IUIAutomationElement;
+use windows::Win32::UI::Accessibility::IUIAutomationNotificationEventHandler;
+use windows::Win32::UI::Accessibility::IUIAutomationNotificationEventHandler_Impl;
+use windows::Win32::UI::Accessibility::IUIAutomationTextEditTextChangedEventHandler;
+use windows::Win32::UI::Accessibility::IUIAutomationTextEditTextChangedEventHandler_Impl;
+use windows::Win32::UI::Accessibility::IUIAutomationTextRange;
+use windows::Win32::UI::Accessibility::TextEditChangeType;
+use windows::Win32::UI::Accessibility;
+use windows::core::implement;
+
+use super::Result;
+use super::UIElement;
+use super::errors::ERR_TYPE;
+use super::errors::Error;
+use super::patterns::UITextRange;
+use super::variants::SafeArray;
+
+/// Defines the kinds of notification raised by `UiaRaiseNotificationEvent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    /// The current element and/or the container has had something added to it that should be presented to the user.
+    ItemAdded,
+    /// The current element has had something removed from inside of it that should be presented to the user.
+    ItemRemoved,
+    /// The current element has a notification that an action was completed.
+    ActionCompleted,
+    /// The current element has a notification that an action was aborted.
+    ActionAborted,
+    /// The current element has a notification not an add, remove, completed, or aborted action.
+    Other
+}
+
+impl TryFrom<Accessibility::NotificationKind> for NotificationKind {
+    type Error = Error;
+
+    fn try_from(kind: Accessibility::NotificationKind) -> Result<Self> {
+        match kind {
+            Accessibility::NotificationKind_ItemAdded => Ok(Self::ItemAdded),
+            Accessibility::NotificationKind_ItemRemoved => Ok(Self::ItemRemoved),
+            Accessibility::NotificationKind_ActionCompleted => Ok(Self::ActionCompleted),
+            Accessibility::NotificationKind_ActionAborted => Ok(Self::ActionAborted),
+            Accessibility::NotificationKind_Other => Ok(Self::Other),
+            _ => Err(Error::new(ERR_TYPE, "Unknown NotificationKind"))
+        }
+    }
+}
+
+impl From<NotificationKind> for Accessibility::NotificationKind {
+    fn from(kind: NotificationKind) -> Self {
+        match kind {
+            NotificationKind::ItemAdded => Accessibility::NotificationKind_ItemAdded,
+            NotificationKind::ItemRemoved => Accessibility::NotificationKind_ItemRemoved,
+            NotificationKind::ActionCompleted => Accessibility::NotificationKind_ActionCompleted,
+            NotificationKind::ActionAborted => Accessibility::NotificationKind_ActionAborted,
+            NotificationKind::Other => Accessibility::NotificationKind_Other,
+        }
+    }
+}
+
+/// Defines how a notification should be processed by the clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationProcessing {
+    /// These notifications should be presented to the user as soon as possible and all of the notifications from this source should be delivered to the user.
+    ImportantAll,
+    /// These notifications should be presented to the user as soon as possible. The most recent notification from this source should replace all other notifications from this source.
+    ImportantMostRecent,
+    /// These notifications should be presented to the user when possible. All of the notifications from this source should be delivered to the user.
+    All,
+    /// These notifications should be presented to the user when possible. The most recent notification from this source should replace all other notifications from this source.
+    MostRecent,
+    /// These notifications should be presented to the user when possible. Don't interrupt the current notification for this one.
+    CurrentThenMostRecent
+}
+
+impl TryFrom<Accessibility::NotificationProcessing> for NotificationProcessing {
+    type Error = Error;
+
+    fn try_from(processing: Accessibility::NotificationProcessing) -> Result<Self> {
+        match processing {
+            Accessibility::NotificationProcessing_ImportantAll => Ok(Self::ImportantAll),
+            Accessibility::NotificationProcessing_ImportantMostRecent => Ok(Self::ImportantMostRecent),
+            Accessibility::NotificationProcessing_All => Ok(Self::All),
+            Accessibility::NotificationProcessing_MostRecent => Ok(Self::MostRecent),
+            Accessibility::NotificationProcessing_CurrentThenMostRecent => Ok(Self::CurrentThenMostRecent),
+            _ => Err(Error::new(ERR_TYPE, "Unknown NotificationProcessing"))
+        }
+    }
+}
+
+impl From<NotificationProcessing> for Accessibility::NotificationProcessing {
+    fn from(processing: NotificationProcessing) -> Self {
+        match processing {
+            NotificationProcessing::ImportantAll => Accessibility::NotificationProcessing_ImportantAll,
+            NotificationProcessing::ImportantMostRecent => Accessibility::NotificationProcessing_ImportantMostRecent,
+            NotificationProcessing::All => Accessibility::NotificationProcessing_All,
+            NotificationProcessing::MostRecent => Accessibility::NotificationProcessing_MostRecent,
+            NotificationProcessing::CurrentThenMostRecent => Accessibility::NotificationProcessing_CurrentThenMostRecent,
+        }
+    }
+}
+
+/// The notification event raised by an element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    /// The kind of the notification.
+    pub kind: NotificationKind,
+    /// The processing order of the notification.
+    pub processing: NotificationProcessing,
+    /// The string to display in the notification message.
+    pub display_string: String,
+    /// The unique non-localized string to identify an action or group of actions.
+    pub activity_id: String
+}
+
+impl Display for Notification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{:?}] {}", self.kind, self.display_string)
+    }
+}
+
+/// Defines a handler for notification events.
+pub trait CustomNotificationEventHandler {
+    /// Handles a notification event raised by `sender`.
+    fn handle(&self, sender: &UIElement, notification: &Notification) -> Result<()>;
+}
+
+/// Defines a handler for text-edit text-changed events.
+pub trait CustomTextEditTextChangedEventHandler {
+    /// Handles a text-edit text-changed event raised by `sender`.
+    /// 
+    /// `event_strings` contains the text strings associated with the change.
+    fn handle(&self, sender: &UIElement, change_type: TextEditChangeType, event_strings: &[String]) -> Result<()>;
+}
+
+/// Defines a handler for active text position changed events.
+pub trait CustomActiveTextPositionChangedEventHandler {
+    /// Handles an active text position changed event raised by `sender`.
+    /// 
+    /// `range` is the text range which contains the new active position, if the provider supplies it.
+    fn handle(&self, sender: &UIElement, range: Option<UITextRange>) -> Result<()>;
+}
+
+#[implement(IUIAutomationNotificationEventHandler)]
+struct NotificationEventHandler {
+    handler: Box<dyn CustomNotificationEventHandler>
+}
+
+impl IUIAutomationNotificationEventHandler_Impl for NotificationEventHandler {
+    fn HandleNotificationEvent(&self, sender: &Option<IUIAutomationElement>, notificationkind: Accessibility::NotificationKind, notificationprocessing: Accessibility::NotificationProcessing, displaystring: &BSTR, activityid: &BSTR) -> windows::core::Result<()> {
+        if let Some(element) = sender {
+            let element = UIElement::from(element.clone());
+            let notification = Notification {
+                kind: notificationkind.try_into()?,
+                processing: notificationprocessing.try_into()?,
+                display_string: displaystring.to_string(),
+                activity_id: activityid.to_string()
+            };
+
+            self.handler.handle(&element, &notification)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[implement(IUIAutomationTextEditTextChangedEventHandler)]
+struct TextEditTextChangedEventHandler {
+    handler: Box<dyn CustomTextEditTextChangedEventHandler>
+}
+
+impl IUIAutomationTextEditTextChangedEventHandler_Impl for TextEditTextChangedEventHandler {
+    fn HandleTextEditTextChangedEvent(&self, sender: &Option<IUIAutomationElement>, texteditchangetype: TextEditChangeType, eventstrings: *const SAFEARRAY) -> windows::core::Result<()> {
+        if let Some(element) = sender {
+            let element = UIElement::from(element.clone());
+            let event_strings = if eventstrings.is_null() {
+                Vec::new()
+            } else {
+                let arr = SafeArray::new(eventstrings as *mut SAFEARRAY, false);
+                arr.into_string_vector()?
+            };
+
+            self.handler.handle(&element, texteditchangetype, &event_strings)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[implement(IUIAutomationActiveTextPositionChangedEventHandler)]
+struct ActiveTextPositionChangedEventHandler {
+    handler: Box<dyn CustomActiveTextPositionChangedEventHandler>
+}
+
+impl IUIAutomationActiveTextPositionChangedEventHandler_Impl for ActiveTextPositionChangedEventHandler {
+    fn HandleActiveTextPositionChangedEvent(&self, sender: &Option<IUIAutomationElement>, range: &Option<IUIAutomationTextRange>) -> windows::core::Result<()> {
+        if let Some(element) = sender {
+            let element = UIElement::from(element.clone());
+            let range = range.as_ref().map(|r| UITextRange::from(r.clone()));
+
+            self.handler.handle(&element, range)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A wrapper for windows `IUIAutomationNotificationEventHandler` interface.
+/// 
+/// Keep this handler to remove it by `UIAutomation::remove_notification_event_handler()`.
+#[derive(Clone)]
+pub struct UINotificationEventHandler {
+    handler: IUIAutomationNotificationEventHandler
+}
+
+impl From<Box<dyn CustomNotificationEventHandler>> for UINotificationEventHandler {
+    fn from(handler: Box<dyn CustomNotificationEventHandler>) -> Self {
+        let handler = NotificationEventHandler {
+            handler
+        };
+        Self {
+            handler: handler.into()
+        }
+    }
+}
+
+impl From<UINotificationEventHandler> for IUIAutomationNotificationEventHandler {
+    fn from(handler: UINotificationEventHandler) -> Self {
+        handler.handler
+    }
+}
+
+impl AsRef<IUIAutomationNotificationEventHandler> for UINotificationEventHandler {
+    fn as_ref(&self) -> &IUIAutomationNotificationEventHandler {
+        &self.handler
+    }
+}
+
+impl Debug for UINotificationEventHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("UINotificationEventHandler")
+    }
+}
+
+/// A wrapper for windows `IUIAutomationTextEditTextChangedEventHandler` interface.
+/// 
+/// Keep this handler to remove it by `UIAutomation::remove_text_edit_text_changed_event_handler()`.
+#[derive(Clone)]
+pub struct UITextEditTextChangedEventHandler {
+    handler: IUIAutomationTextEditTextChangedEventHandler
+}
+
+impl From<Box<dyn CustomTextEditTextChangedEventHandler>> for UITextEditTextChangedEventHandler {
+    fn from(handler: Box<dyn CustomTextEditTextChangedEventHandler>) -> Self {
+        let handler = TextEditTextChangedEventHandler {
+            handler
+        };
+        Self {
+            handler: handler.into()
+        }
+    }
+}
+
+impl From<UITextEditTextChangedEventHandler> for IUIAutomationTextEditTextChangedEventHandler {
+    fn from(handler: UITextEditTextChangedEventHandler) -> Self {
+        handler.handler
+    }
+}
+
+impl AsRef<IUIAutomationTextEditTextChangedEventHandler> for UITextEditTextChangedEventHandler {
+    fn as_ref(&self) -> &IUIAutomationTextEditTextChangedEventHandler {
+        &self.handler
+    }
+}
+
+impl Debug for UITextEditTextChangedEventHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("UITextEditTextChangedEventHandler")
+    }
+}
+
+/// A wrapper for windows `IUIAutomationActiveTextPositionChangedEventHandler` interface.
+/// 
+/// Keep this handler to remove it by `UIAutomation::remove_active_text_position_changed_event_handler()`.
+#[derive(Clone)]
+pub struct UIActiveTextPositionChangedEventHandler {
+    handler: IUIAutomationActiveTextPositionChangedEventHandler
+}
+
+impl From<Box<dyn CustomActiveTextPositionChangedEventHandler>> for UIActiveTextPositionChangedEventHandler {
+    fn from(handler: Box<dyn CustomActiveTextPositionChangedEventHandler>) -> Self {
+        let handler = ActiveTextPositionChangedEventHandler {
+            handler
+        };
+        Self {
+            handler: handler.into()
+        }
+    }
+}
+
+impl From<UIActiveTextPositionChangedEventHandler> for IUIAutomationActiveTextPositionChangedEventHandler {
+    fn from(handler: UIActiveTextPositionChangedEventHandler) -> Self {
+        handler.handler
+    }
+}
+
+impl AsRef<IUIAutomationActiveTextPositionChangedEventHandler> for UIActiveTextPositionChangedEventHandler {
+    fn as_ref(&self) -> &IUIAutomationActiveTextPositionChangedEventHandler {
+        &self.handler
+    }
+}
+
+impl Debug for UIActiveTextPositionChangedEventHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("UIActiveTextPositionChangedEventHandler")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use windows::Win32::UI::Accessibility;
+
+    use crate::events::NotificationKind;
+    use crate::events::NotificationProcessing;
+
+    #[test]
+    fn test_notification_kind() {
+        let kinds = [NotificationKind::ItemAdded, NotificationKind::ItemRemoved, NotificationKind::ActionCompleted, NotificationKind::ActionAborted, NotificationKind::Other];
+        for kind in kinds {
+            let raw: Accessibility::NotificationKind = kind.into();
+            assert_eq!(NotificationKind::try_from(raw).unwrap(), kind);
+        }
+
+        assert!(NotificationKind::try_from(Accessibility::NotificationKind(99)).is_err());
+    }
+
+    #[test]
+    fn test_notification_processing() {
+        let raw = Accessibility::NotificationProcessing_ImportantMostRecent;
+        assert_eq!(NotificationProcessing::try_from(raw).unwrap(), NotificationProcessing::ImportantMostRecent);
+        assert!(NotificationProcessing::try_from(Accessibility::NotificationProcessing(-1)).is_err());
+    }
+}
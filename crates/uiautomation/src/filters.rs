@@ -110,6 +110,18 @@ impl MatcherFilter for ControlTypeFilter {
     }
 }
 
+#[derive(Debug, Default)]
+pub struct ProcessIdFilter {
+    pub process_id: i32
+}
+
+impl MatcherFilter for ProcessIdFilter {
+    fn judge(&self, element: &UIElement) -> Result<bool> {
+        let process_id = element.get_process_id()?;
+        Ok(self.process_id == process_id)
+    }
+}
+
 pub struct FnFilter<F> where F: Fn(&UIElement) -> Result<bool> {
     pub filter: Box<F>
 }
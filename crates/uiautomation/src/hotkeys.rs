@@ -0,0 +1,429 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+use super::inputs::KeyCode;
+use super::inputs::KeyExpression;
+use super::inputs::KeyItem;
+use super::inputs::KeyParseError;
+use super::inputs::SpecialKey;
+use super::inputs::get_key_names;
+use super::inputs::get_virtual_key;
+use super::layouts::MODIFIER_ALT;
+use super::layouts::MODIFIER_CTRL;
+use super::layouts::MODIFIER_SHIFT;
+use super::layouts::MODIFIER_WIN;
+
+/// The modifiers in the canonical order of menu notation, with their display names and hold keys.
+const MODIFIERS: [(u8, &str, &str); 4] = [
+    (MODIFIER_CTRL, "Ctrl", "CTRL"),
+    (MODIFIER_ALT, "Alt", "ALT"),
+    (MODIFIER_SHIFT, "Shift", "SHIFT"),
+    (MODIFIER_WIN, "Win", "WIN")
+];
+
+/// The punctuation keys of the US keyboard, which are named by their characters or words.
+const PUNCTUATIONS: [(char, &str, VIRTUAL_KEY); 13] = [
+    ('+', "Plus", VK_OEM_PLUS), ('=', "=", VK_OEM_PLUS), ('-', "Minus", VK_OEM_MINUS),
+    (',', "Comma", VK_OEM_COMMA), ('.', "Period", VK_OEM_PERIOD), (';', ";", VK_OEM_1),
+    ('/', "/", VK_OEM_2), ('`', "`", VK_OEM_3), ('[', "[", VK_OEM_4), ('\\', "\\", VK_OEM_5),
+    (']', "]", VK_OEM_6), ('\'', "'", VK_OEM_7), ('*', "*", VK_MULTIPLY)
+];
+
+/// The names of the common keys in menu notation.
+const KEY_NAMES: [(VIRTUAL_KEY, &str); 20] = [
+    (VK_BACK, "Backspace"), (VK_TAB, "Tab"), (VK_RETURN, "Enter"), (VK_ESCAPE, "Esc"), (VK_SPACE, "Space"),
+    (VK_PRIOR, "PgUp"), (VK_NEXT, "PgDn"), (VK_END, "End"), (VK_HOME, "Home"), (VK_LEFT, "Left"),
+    (VK_UP, "Up"), (VK_RIGHT, "Right"), (VK_DOWN, "Down"), (VK_INSERT, "Ins"), (VK_DELETE, "Del"),
+    (VK_PAUSE, "Pause"), (VK_APPS, "Apps"), (VK_SNAPSHOT, "PrtSc"), (VK_CAPITAL, "CapsLock"), (VK_NUMLOCK, "NumLock")
+];
+
+/// A keyboard shortcut in menu notation, such as `Ctrl+Shift+S` or `Alt+F4`.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use uiautomation::hotkeys::Hotkey;
+/// use uiautomation::layouts::MODIFIER_ALT;
+/// use windows::Win32::UI::Input::KeyboardAndMouse::VK_F4;
+/// 
+/// let hotkey = Hotkey::parse("alt + f4").unwrap();
+/// assert_eq!(hotkey, Hotkey::new(MODIFIER_ALT, VK_F4));
+/// assert_eq!(hotkey.to_string(), "Alt+F4");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hotkey {
+    modifiers: u8,
+    key: VIRTUAL_KEY
+}
+
+impl Hotkey {
+    /// Creates a hotkey of the `modifiers` bit set and the virtual `key`.
+    pub fn new(modifiers: u8, key: VIRTUAL_KEY) -> Self {
+        Self {
+            modifiers,
+            key
+        }
+    }
+
+    /// Parses a single hotkey, such as `Ctrl+Shift+S`.
+    pub fn parse(hotkey: &str) -> core::result::Result<Self, KeyParseError> {
+        let sequence = HotkeySequence::parse(hotkey)?;
+        match sequence.hotkeys.as_slice() {
+            [hotkey] => Ok(*hotkey),
+            _ => Err(KeyParseError::new(0, hotkey, "Not a single hotkey"))
+        }
+    }
+
+    /// Retrieves the modifiers bit set, which combines `MODIFIER_CTRL`, `MODIFIER_ALT`, `MODIFIER_SHIFT` and `MODIFIER_WIN`.
+    pub fn get_modifiers(&self) -> u8 {
+        self.modifiers
+    }
+
+    /// Retrieves the virtual key which is pressed with the modifiers.
+    pub fn get_key(&self) -> VIRTUAL_KEY {
+        self.key
+    }
+
+    /// Converts the hotkey to the key expression used by `Keyboard::send_keys()`.
+    pub fn to_expression(&self) -> KeyExpression {
+        KeyExpression::from(self.create_items())
+    }
+
+    fn create_items(&self) -> Vec<KeyItem> {
+        let mut items: Vec<KeyItem> = MODIFIERS.iter()
+            .filter(|(modifier, _, _)| self.modifiers & modifier != 0)
+            .map(|(_, _, name)| {
+                let key = get_virtual_key(name).unwrap_or_default();
+                KeyItem::HoldKey(SpecialKey::new(name.to_string(), KeyCode::Virtual(key), 1))
+            })
+            .collect();
+        items.push(KeyItem::Key(SpecialKey::new(format!("VK 0x{:02X}", self.key.0), KeyCode::Virtual(self.key), 1)));
+        items
+    }
+}
+
+impl FromStr for Hotkey {
+    type Err = KeyParseError;
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for Hotkey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (modifier, name, _) in MODIFIERS {
+            if self.modifiers & modifier != 0 {
+                write!(f, "{}+", name)?;
+            }
+        }
+        write!(f, "{}", get_key_display_name(self.key))
+    }
+}
+
+/// A sequence of hotkeys, such as `Ctrl+K, Ctrl+C`.
+/// 
+/// The keys of a hotkey are separated by `+`, and the hotkeys are separated by commas or spaces.
+/// Key names are case insensitive. Letters, digits, function keys, punctuation characters,
+/// the names used by key expressions and common menu names such as `Del` or `PgUp` are supported.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use uiautomation::hotkeys::HotkeySequence;
+/// 
+/// let sequence = HotkeySequence::parse("ctrl+k ctrl+c").unwrap();
+/// assert_eq!(sequence.get_hotkeys().len(), 2);
+/// assert_eq!(sequence.to_string(), "Ctrl+K, Ctrl+C");
+/// assert_eq!(sequence.to_expression().to_string(), "{CTRL}{VK 0x4B}{CTRL}{VK 0x43}");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HotkeySequence {
+    hotkeys: Vec<Hotkey>
+}
+
+impl HotkeySequence {
+    /// Parses a sequence of hotkeys.
+    /// 
+    /// The error reports the character offset and the offending token.
+    pub fn parse(sequence: &str) -> core::result::Result<Self, KeyParseError> {
+        let chars: Vec<char> = sequence.chars().collect();
+        let mut pos = 0;
+        let mut hotkeys: Vec<Hotkey> = Vec::new();
+
+        loop {
+            while pos < chars.len() && (chars[pos].is_whitespace() || chars[pos] == ',') {
+                pos += 1;
+            }
+            if pos >= chars.len() {
+                break;
+            }
+
+            let mut modifiers = 0u8;
+            loop {
+                skip_whitespaces(&chars, &mut pos);
+                let start = pos;
+                let token = read_key_token(&chars, &mut pos);
+                if token.is_empty() {
+                    return Err(KeyParseError::new(start, "+", "Missing key"));
+                }
+
+                skip_whitespaces(&chars, &mut pos);
+                if chars.get(pos) == Some(&'+') {
+                    let modifier = match get_modifier(&token) {
+                        Some(modifier) => modifier,
+                        None => return Err(KeyParseError::new(start, &token, "Not a modifier key"))
+                    };
+                    if modifiers & modifier != 0 {
+                        return Err(KeyParseError::new(start, &token, "Duplicate modifier key"));
+                    }
+                    modifiers |= modifier;
+                    pos += 1;
+                } else {
+                    match lookup_hotkey_key(&token) {
+                        Some(key) => hotkeys.push(Hotkey::new(modifiers, key)),
+                        None => return Err(KeyParseError::new(start, &token, "Unknown key"))
+                    };
+                    break;
+                }
+            }
+        }
+
+        if hotkeys.is_empty() {
+            Err(KeyParseError::new(0, sequence, "Empty hotkey"))
+        } else {
+            Ok(Self {
+                hotkeys
+            })
+        }
+    }
+
+    /// Retrieves the hotkeys of the sequence.
+    pub fn get_hotkeys(&self) -> &[Hotkey] {
+        self.hotkeys.as_slice()
+    }
+
+    /// Converts the sequence to the key expression used by `Keyboard::send_keys()`.
+    pub fn to_expression(&self) -> KeyExpression {
+        let items: Vec<KeyItem> = self.hotkeys.iter().flat_map(|hotkey| hotkey.create_items()).collect();
+        KeyExpression::from(items)
+    }
+}
+
+impl From<Vec<Hotkey>> for HotkeySequence {
+    fn from(hotkeys: Vec<Hotkey>) -> Self {
+        Self {
+            hotkeys
+        }
+    }
+}
+
+impl FromStr for HotkeySequence {
+    type Err = KeyParseError;
+
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for HotkeySequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, hotkey) in self.hotkeys.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", hotkey)?;
+        }
+        Ok(())
+    }
+}
+
+fn skip_whitespaces(chars: &[char], pos: &mut usize) {
+    while *pos < chars.len() && chars[*pos].is_whitespace() {
+        *pos += 1;
+    }
+}
+
+/// Reads a key name, or a single punctuation character such as `+` or `,`.
+fn read_key_token(chars: &[char], pos: &mut usize) -> String {
+    let start = *pos;
+    while *pos < chars.len() && (chars[*pos].is_alphanumeric() || chars[*pos] == '_') {
+        *pos += 1;
+    }
+    if *pos == start && *pos < chars.len() && !chars[*pos].is_whitespace() {
+        *pos += 1;
+    }
+
+    chars[start..*pos].iter().collect()
+}
+
+fn get_modifier(name: &str) -> Option<u8> {
+    match get_virtual_key(name)? {
+        VK_CONTROL | VK_LCONTROL | VK_RCONTROL => Some(MODIFIER_CTRL),
+        VK_MENU | VK_LMENU | VK_RMENU => Some(MODIFIER_ALT),
+        VK_SHIFT | VK_LSHIFT | VK_RSHIFT => Some(MODIFIER_SHIFT),
+        VK_LWIN | VK_RWIN => Some(MODIFIER_WIN),
+        _ => None
+    }
+}
+
+pub(crate) fn lookup_hotkey_key(token: &str) -> Option<VIRTUAL_KEY> {
+    let mut chars = token.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        if ch.is_ascii_alphanumeric() {
+            return Some(VIRTUAL_KEY(ch.to_ascii_uppercase() as u16));
+        }
+        if let Some((_, _, key)) = PUNCTUATIONS.iter().find(|(c, _, _)| *c == ch) {
+            return Some(*key);
+        }
+    }
+
+    if let Some((_, _, key)) = PUNCTUATIONS.iter().find(|(_, name, _)| name.eq_ignore_ascii_case(token)) {
+        return Some(*key);
+    }
+    if let Some((key, _)) = KEY_NAMES.iter().find(|(_, name)| name.eq_ignore_ascii_case(token)) {
+        return Some(*key);
+    }
+
+    let name = token.to_uppercase();
+    match name.as_str() {
+        "PAGEUP" => Some(VK_PRIOR),
+        "PAGEDOWN" => Some(VK_NEXT),
+        "BREAK" => Some(VK_PAUSE),
+        _ => {
+            if let Some(digit) = name.strip_prefix("NUM").and_then(|d| d.parse::<u16>().ok()).filter(|d| *d < 10) {
+                Some(VIRTUAL_KEY(VK_NUMPAD0.0 + digit))
+            } else if let Some(hex) = name.strip_prefix("0X") {
+                u16::from_str_radix(hex, 16).ok().filter(|code| *code > 0 && *code < 0xFF).map(VIRTUAL_KEY)
+            } else {
+                get_virtual_key(token)
+            }
+        }
+    }
+}
+
+/// Retrieves the name of the key in menu notation, such as `S`, `F4`, `Del` or `Num0`.
+fn get_key_display_name(key: VIRTUAL_KEY) -> String {
+    let code = key.0;
+    if (0x30..=0x39).contains(&code) || (0x41..=0x5A).contains(&code) {
+        return char::from(code as u8).to_string();
+    }
+    if (VK_F1.0..=VK_F24.0).contains(&code) {
+        return format!("F{}", code - VK_F1.0 + 1);
+    }
+    if (VK_NUMPAD0.0..=VK_NUMPAD9.0).contains(&code) {
+        return format!("Num{}", code - VK_NUMPAD0.0);
+    }
+    if let Some((_, name, _)) = PUNCTUATIONS.iter().find(|(_, _, k)| *k == key) {
+        return name.to_string();
+    }
+    if let Some((_, name)) = KEY_NAMES.iter().find(|(k, _)| *k == key) {
+        return name.to_string();
+    }
+    if let Some((_, name, _)) = MODIFIERS.iter().find(|(_, _, name)| get_virtual_key(name) == Some(key)) {
+        return name.to_string();
+    }
+
+    // the shortest name used by key expressions, such as `Volume_Mute`.
+    let names = get_key_names();
+    let name = names.iter().filter(|name| get_virtual_key(name) == Some(key)).min_by_key(|name| name.len());
+    match name {
+        Some(name) => title_case(name),
+        None => format!("0x{:02X}", code)
+    }
+}
+
+fn title_case(name: &str) -> String {
+    name.split('_').map(|word| {
+        let mut chars = word.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars.flat_map(|c| c.to_lowercase())).collect(),
+            None => String::new()
+        }
+    }).collect::<Vec<String>>().join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+    use crate::hotkeys::Hotkey;
+    use crate::hotkeys::HotkeySequence;
+    use crate::inputs::InputEvent;
+    use crate::inputs::KeyCode;
+    use crate::inputs::Keyboard;
+    use crate::inputs::RecordingSink;
+    use crate::layouts::MODIFIER_ALT;
+    use crate::layouts::MODIFIER_CTRL;
+    use crate::layouts::MODIFIER_SHIFT;
+    use crate::layouts::MODIFIER_WIN;
+
+    #[test]
+    fn test_parse_hotkey() {
+        assert_eq!(Hotkey::parse("Ctrl+Shift+S").unwrap(), Hotkey::new(MODIFIER_CTRL | MODIFIER_SHIFT, VK_S));
+        assert_eq!(Hotkey::parse("Alt+F4").unwrap(), Hotkey::new(MODIFIER_ALT, VK_F4));
+        assert_eq!(Hotkey::parse("win+r").unwrap(), Hotkey::new(MODIFIER_WIN, VK_R));
+        assert_eq!(Hotkey::parse("Ctrl + Alt + Del").unwrap(), Hotkey::new(MODIFIER_CTRL | MODIFIER_ALT, VK_DELETE));
+        assert_eq!(Hotkey::parse("Ctrl++").unwrap(), Hotkey::new(MODIFIER_CTRL, VK_OEM_PLUS));
+        assert_eq!(Hotkey::parse("Ctrl+,").unwrap(), Hotkey::new(MODIFIER_CTRL, VK_OEM_COMMA));
+        assert_eq!(Hotkey::parse("Shift+PgDn").unwrap(), Hotkey::new(MODIFIER_SHIFT, VK_NEXT));
+        assert_eq!(Hotkey::parse("Enter").unwrap(), Hotkey::new(0, VK_RETURN));
+    }
+
+    #[test]
+    fn test_parse_sequence() {
+        let expected = vec![Hotkey::new(MODIFIER_CTRL, VK_K), Hotkey::new(MODIFIER_CTRL, VK_C)];
+        assert_eq!(HotkeySequence::parse("Ctrl+K, Ctrl+C").unwrap().get_hotkeys(), expected.as_slice());
+        assert_eq!(HotkeySequence::parse("Ctrl+K Ctrl+C").unwrap().get_hotkeys(), expected.as_slice());
+        assert_eq!(HotkeySequence::parse("Ctrl+K,Ctrl+C").unwrap().get_hotkeys(), expected.as_slice());
+        assert!(Hotkey::parse("Ctrl+K, Ctrl+C").is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = HotkeySequence::parse("Ctrl+Shoft+S").unwrap_err();
+        assert_eq!(err.get_offset(), 5);
+        assert_eq!(err.get_token(), "Shoft");
+
+        let err = HotkeySequence::parse("Ctrl+K, Q+C").unwrap_err();
+        assert_eq!(err.get_offset(), 8);
+        assert_eq!(err.get_message(), "Not a modifier key");
+
+        assert!(HotkeySequence::parse("Ctrl+Ctrl+S").is_err());
+        assert!(HotkeySequence::parse("Ctrl+").is_err());
+        assert!(HotkeySequence::parse(" , ").is_err());
+    }
+
+    #[test]
+    fn test_format_hotkey() {
+        for hotkey in ["Ctrl+Shift+S", "Alt+F4", "Win+R", "Ctrl+Alt+Del", "Ctrl+Plus", "Ctrl+Comma", "Shift+Num5", "Ctrl+/", "Ctrl+Shift", "Volume_Mute"] {
+            assert_eq!(Hotkey::parse(hotkey).unwrap().to_string(), hotkey);
+        }
+        assert_eq!(Hotkey::parse("shift+ctrl+s").unwrap().to_string(), "Ctrl+Shift+S");
+        assert_eq!(Hotkey::parse("Ctrl+=").unwrap().to_string(), "Ctrl+Plus");
+        assert_eq!(HotkeySequence::parse("ctrl+k ctrl+c").unwrap().to_string(), "Ctrl+K, Ctrl+C");
+    }
+
+    #[test]
+    fn test_press_hotkey() {
+        let keyboard = Keyboard::with_sink(RecordingSink::new());
+        keyboard.press_hotkey("Ctrl+Shift+S, Alt+F4").unwrap();
+        assert_eq!(
+            keyboard.get_sink().get_events(),
+            vec![
+                InputEvent::KeyDown(KeyCode::Virtual(VK_CONTROL)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_SHIFT)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_S)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_S)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_SHIFT)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_CONTROL)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_MENU)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_F4)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_F4)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_MENU))
+            ]
+        );
+    }
+}
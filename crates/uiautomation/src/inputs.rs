@@ -1,23 +1,88 @@
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::cell::RefCell;
 use std::cmp::max;
-use std::cmp::min;
+use std::fmt::Display;
 use std::mem;
-use std::str::Chars;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
 use std::thread::sleep;
 use std::time::Duration;
+use std::time::Instant;
 
 use phf::phf_map;
 use phf::phf_set;
+use rand::rngs::SmallRng;
+use windows::Win32::Foundation::HWND;
+use windows::Win32::Foundation::LPARAM;
+use windows::Win32::Foundation::POINT;
+use windows::Win32::Foundation::WPARAM;
+use windows::Win32::Graphics::Gdi::ScreenToClient;
 use windows::Win32::UI::Input::KeyboardAndMouse::*;
 use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
 use windows::Win32::UI::WindowsAndMessaging::GetSystemMetrics;
+use windows::Win32::UI::WindowsAndMessaging::MAPVK_VK_TO_VSC;
+use windows::Win32::UI::WindowsAndMessaging::MAPVK_VSC_TO_VK_EX;
+use windows::Win32::UI::WindowsAndMessaging::MK_CONTROL;
+use windows::Win32::UI::WindowsAndMessaging::MK_LBUTTON;
+use windows::Win32::UI::WindowsAndMessaging::MK_MBUTTON;
+use windows::Win32::UI::WindowsAndMessaging::MK_RBUTTON;
+use windows::Win32::UI::WindowsAndMessaging::MK_SHIFT;
+use windows::Win32::UI::WindowsAndMessaging::MK_XBUTTON1;
+use windows::Win32::UI::WindowsAndMessaging::MK_XBUTTON2;
+use windows::Win32::UI::WindowsAndMessaging::PostMessageW;
+use windows::Win32::UI::WindowsAndMessaging::SetCursorPos;
 use windows::Win32::UI::WindowsAndMessaging::SM_CXSCREEN;
 use windows::Win32::UI::WindowsAndMessaging::SM_CYSCREEN;
-use windows::Win32::UI::WindowsAndMessaging::SetCursorPos;
+use windows::Win32::UI::WindowsAndMessaging::WM_CHAR;
+use windows::Win32::UI::WindowsAndMessaging::WM_KEYDOWN;
+use windows::Win32::UI::WindowsAndMessaging::WM_KEYUP;
+use windows::Win32::UI::WindowsAndMessaging::WM_LBUTTONDBLCLK;
+use windows::Win32::UI::WindowsAndMessaging::WM_LBUTTONDOWN;
+use windows::Win32::UI::WindowsAndMessaging::WM_LBUTTONUP;
+use windows::Win32::UI::WindowsAndMessaging::WM_MBUTTONDBLCLK;
+use windows::Win32::UI::WindowsAndMessaging::WM_MBUTTONDOWN;
+use windows::Win32::UI::WindowsAndMessaging::WM_MBUTTONUP;
+use windows::Win32::UI::WindowsAndMessaging::WM_MOUSEHWHEEL;
+use windows::Win32::UI::WindowsAndMessaging::WM_MOUSEMOVE;
+use windows::Win32::UI::WindowsAndMessaging::WM_MOUSEWHEEL;
+use windows::Win32::UI::WindowsAndMessaging::WM_RBUTTONDBLCLK;
+use windows::Win32::UI::WindowsAndMessaging::WM_RBUTTONDOWN;
+use windows::Win32::UI::WindowsAndMessaging::WM_RBUTTONUP;
+use windows::Win32::UI::WindowsAndMessaging::WM_SYSCHAR;
+use windows::Win32::UI::WindowsAndMessaging::WM_SYSKEYDOWN;
+use windows::Win32::UI::WindowsAndMessaging::WM_SYSKEYUP;
+use windows::Win32::UI::WindowsAndMessaging::WM_XBUTTONDBLCLK;
+use windows::Win32::UI::WindowsAndMessaging::WM_XBUTTONDOWN;
+use windows::Win32::UI::WindowsAndMessaging::WM_XBUTTONUP;
+use windows::Win32::UI::WindowsAndMessaging::XBUTTON1;
+use windows::Win32::UI::WindowsAndMessaging::XBUTTON2;
 
 use super::errors::ERR_FORMAT;
+use super::hotkeys::Hotkey;
+use super::hotkeys::HotkeySequence;
 use super::Error;
 use super::Result;
+use super::layouts::KeyStroke;
+use super::layouts::KeyboardLayout;
+use super::layouts::MODIFIER_ALT;
+use super::layouts::MODIFIER_CTRL;
+use super::layouts::MODIFIER_SHIFT;
+use super::screens::Monitor;
+use super::screens::clamp_to_monitors;
+use super::screens::get_virtual_screen;
+use super::screens::to_absolute_coordinates;
+use super::trajectory::Trajectory;
+use super::typing::ScheduledEvent;
+use super::typing::Stroke;
+use super::typing::TypingProfile;
+use super::typing::schedule_interval;
+use super::trajectory::create_rng;
+use super::types::Handle;
 use super::types::Point;
+use super::types::Rect;
 
 const VIRTUAL_KEYS: phf::Map<&'static str, VIRTUAL_KEY> = phf_map! {
     "CONTROL" => VK_CONTROL, "CTRL" => VK_CONTROL, "LCONTROL" => VK_LCONTROL, "LCTRL" => VK_LCONTROL, "RCONTROL" => VK_RCONTROL, "RCTRL" => VK_RCONTROL,
@@ -33,6 +98,22 @@ const VIRTUAL_KEYS: phf::Map<&'static str, VIRTUAL_KEY> = phf_map! {
     "F1" => VK_F1, "F2" => VK_F2, "F3" => VK_F3, "F4" => VK_F4, "F5" => VK_F5, "F6" => VK_F6, "F7" => VK_F7, "F8" => VK_F8, "F9" => VK_F9, "F10" => VK_F10,
     "F11" => VK_F11, "F12" => VK_F12, "F13" => VK_F13, "F14" => VK_F14, "F15" => VK_F15, "F16" => VK_F16, "F17" => VK_F17, "F18" => VK_F18, "F19" => VK_F19,
     "F20" => VK_F20, "F21" => VK_F21, "F22" => VK_F22, "F23" => VK_F23, "F24" => VK_F24,
+    "NUMPAD0" => VK_NUMPAD0, "NUMPAD1" => VK_NUMPAD1, "NUMPAD2" => VK_NUMPAD2, "NUMPAD3" => VK_NUMPAD3, "NUMPAD4" => VK_NUMPAD4,
+    "NUMPAD5" => VK_NUMPAD5, "NUMPAD6" => VK_NUMPAD6, "NUMPAD7" => VK_NUMPAD7, "NUMPAD8" => VK_NUMPAD8, "NUMPAD9" => VK_NUMPAD9,
+    "ADD" => VK_ADD, "SUBTRACT" => VK_SUBTRACT, "MULTIPLY" => VK_MULTIPLY, "DIVIDE" => VK_DIVIDE, "DECIMAL" => VK_DECIMAL, "SEPARATOR" => VK_SEPARATOR,
+    "NUMLOCK" => VK_NUMLOCK, "SCROLL" => VK_SCROLL, "SCROLLLOCK" => VK_SCROLL, "CAPSLOCK" => VK_CAPITAL,
+    "APPS" => VK_APPS, "SNAPSHOT" => VK_SNAPSHOT, "PRINTSCREEN" => VK_SNAPSHOT, "PRTSC" => VK_SNAPSHOT, "SLEEP" => VK_SLEEP,
+    "CLEAR" => VK_CLEAR, "SELECT" => VK_SELECT, "EXECUTE" => VK_EXECUTE, "HELP" => VK_HELP, "BACKSPACE" => VK_BACK, "INS" => VK_INSERT, "DEL" => VK_DELETE,
+    "VOLUME_MUTE" => VK_VOLUME_MUTE, "VOLUME_DOWN" => VK_VOLUME_DOWN, "VOLUME_UP" => VK_VOLUME_UP,
+    "MEDIA_NEXT_TRACK" => VK_MEDIA_NEXT_TRACK, "MEDIA_PREV_TRACK" => VK_MEDIA_PREV_TRACK, "MEDIA_STOP" => VK_MEDIA_STOP, "MEDIA_PLAY_PAUSE" => VK_MEDIA_PLAY_PAUSE,
+    "BROWSER_BACK" => VK_BROWSER_BACK, "BROWSER_FORWARD" => VK_BROWSER_FORWARD, "BROWSER_REFRESH" => VK_BROWSER_REFRESH, "BROWSER_STOP" => VK_BROWSER_STOP,
+    "BROWSER_SEARCH" => VK_BROWSER_SEARCH, "BROWSER_FAVORITES" => VK_BROWSER_FAVORITES, "BROWSER_HOME" => VK_BROWSER_HOME,
+    "LAUNCH_MAIL" => VK_LAUNCH_MAIL, "LAUNCH_MEDIA_SELECT" => VK_LAUNCH_MEDIA_SELECT, "LAUNCH_APP1" => VK_LAUNCH_APP1, "LAUNCH_APP2" => VK_LAUNCH_APP2,
+    "OEM_1" => VK_OEM_1, "OEM_2" => VK_OEM_2, "OEM_3" => VK_OEM_3, "OEM_4" => VK_OEM_4, "OEM_5" => VK_OEM_5, "OEM_6" => VK_OEM_6, "OEM_7" => VK_OEM_7, "OEM_8" => VK_OEM_8,
+    "OEM_PLUS" => VK_OEM_PLUS, "OEM_COMMA" => VK_OEM_COMMA, "OEM_MINUS" => VK_OEM_MINUS, "OEM_PERIOD" => VK_OEM_PERIOD, "OEM_102" => VK_OEM_102, "OEM_CLEAR" => VK_OEM_CLEAR,
+    "KANA" => VK_KANA, "HANGUL" => VK_HANGUL, "JUNJA" => VK_JUNJA, "FINAL" => VK_FINAL, "HANJA" => VK_HANJA, "KANJI" => VK_KANJI,
+    "CONVERT" => VK_CONVERT, "NONCONVERT" => VK_NONCONVERT, "ACCEPT" => VK_ACCEPT, "MODECHANGE" => VK_MODECHANGE, "PROCESSKEY" => VK_PROCESSKEY,
+    "ATTN" => VK_ATTN, "CRSEL" => VK_CRSEL, "EXSEL" => VK_EXSEL, "EREOF" => VK_EREOF, "PLAY" => VK_PLAY, "ZOOM" => VK_ZOOM, "PA1" => VK_PA1,
 };
 
 const HOLD_KEYS: phf::Set<&'static str> = phf_set! {
@@ -42,21 +123,32 @@ const HOLD_KEYS: phf::Set<&'static str> = phf_set! {
     "WIN", "WINDOWS", "LWIN", "LWINDOWS", "RWIN", "RWINDOWS"
 };
 
+/// The modifier keys which are checked and released, the sided keys before the generic ones.
+const MODIFIER_KEYS: [VIRTUAL_KEY; 11] = [
+    VK_LSHIFT, VK_RSHIFT, VK_SHIFT, VK_LCONTROL, VK_RCONTROL, VK_CONTROL, VK_LMENU, VK_RMENU, VK_MENU, VK_LWIN, VK_RWIN
+];
+
+/// The lock keys which are normalized before typing.
+const LOCK_KEYS: [VIRTUAL_KEY; 3] = [VK_CAPITAL, VK_NUMLOCK, VK_SCROLL];
+
+/// The wheel delta of one notch.
+const WHEEL_DELTA: i32 = 120;
+
 const KEYEVENTF_KEYDOWN: KEYBD_EVENT_FLAGS = KEYBD_EVENT_FLAGS(0);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum InputItem {
     HoldKey(VIRTUAL_KEY),
     VirtualKey(VIRTUAL_KEY),
+    ScanCode(u16),
+    KeyDown(KeyCode),
+    KeyUp(KeyCode),
     Character(char),
 }
 
 impl InputItem {
-    fn is_holdkey(&self) -> bool {
-        match self {
-            Self::HoldKey(_) => true,
-            _ => false,
-        }
+    fn is_key_state(&self) -> bool {
+        matches!(self, Self::KeyDown(_) | Self::KeyUp(_))
     }
 }
 
@@ -82,6 +174,10 @@ impl Input {
         !self.items.is_empty()
     }
 
+    fn has_chars(&self) -> bool {
+        self.items.iter().any(|item| matches!(item, InputItem::Character(_)))
+    }
+
     fn is_holdkey_only(&self) -> bool {
         !self.holdkeys.is_empty() && self.items.is_empty()
     }
@@ -102,499 +198,2053 @@ impl Input {
         }
     }
 
-    fn create_inputs(&self) -> Result<Vec<INPUT>> {
-        let mut inputs: Vec<INPUT> = Vec::new();
+    /// Creates the keystrokes of this input.
+    /// 
+    /// `pressed` is the keys pressed by `{key down}` and is updated by the key state items.
+    fn create_strokes(&self, typer: &CharTyper, pressed: &mut Vec<KeyCode>) -> Vec<Stroke> {
+        let mut strokes: Vec<Stroke> = Vec::new();
 
-        for holdkey in &self.holdkeys {
-            let input = Self::create_virtual_key(*holdkey, KEYEVENTF_KEYDOWN);
-            inputs.push(input);
+        if self.has_holdkey() {
+            let events = self.holdkeys.iter().map(|key| InputEvent::KeyDown(KeyCode::Virtual(*key))).collect();
+            strokes.push(Stroke::new(events, None));
         }
 
         for item in &self.items {
-            match item {
-                InputItem::VirtualKey(key) => {
-                    inputs.push(Self::create_virtual_key(*key, KEYEVENTF_KEYDOWN));
-                    inputs.push(Self::create_virtual_key(*key, KEYEVENTF_KEYUP));
+            let stroke = match item {
+                InputItem::VirtualKey(key) => Stroke::new(vec![
+                    InputEvent::KeyDown(KeyCode::Virtual(*key)),
+                    InputEvent::KeyUp(KeyCode::Virtual(*key))
+                ], None),
+                InputItem::ScanCode(code) => Stroke::new(vec![
+                    InputEvent::KeyDown(KeyCode::Scan(*code)),
+                    InputEvent::KeyUp(KeyCode::Scan(*code))
+                ], None),
+                InputItem::KeyDown(key) => {
+                    pressed.push(*key);
+                    Stroke::new(vec![InputEvent::KeyDown(*key)], None)
+                },
+                InputItem::KeyUp(key) => {
+                    pressed.retain(|k| k != key);
+                    Stroke::new(vec![InputEvent::KeyUp(*key)], None)
                 },
                 InputItem::Character(ch) => {
-                    let mut buffer = [0; 2];
-                    let chars = ch.encode_utf16(&mut buffer);
-                    for ch_u16 in chars {
-                        let keys = Self::create_char_key(*ch_u16, self.has_holdkey());
-                        inputs.extend(keys);
-                    }
+                    let events = typer.create_char_events(*ch, self.has_holdkey() || !pressed.is_empty());
+                    Stroke::new(events, Some(*ch))
                 },
-                _ => (),
-            }
+                InputItem::HoldKey(_) => continue,
+            };
+            strokes.push(stroke);
         }
 
-        for holdkey in (&self.holdkeys).iter().rev() {
-            let input = Self::create_virtual_key(*holdkey, KEYEVENTF_KEYUP);
-            inputs.push(input);
+        if self.has_holdkey() {
+            let events = self.holdkeys.iter().rev().map(|key| InputEvent::KeyUp(KeyCode::Virtual(*key))).collect();
+            strokes.push(Stroke::new(events, None));
         }
 
-        Ok(inputs)
+        strokes
     }
+}
 
-    fn create_virtual_key(key: VIRTUAL_KEY, flags: KEYBD_EVENT_FLAGS) -> INPUT {
-        INPUT {
-            r#type: INPUT_KEYBOARD,
-            Anonymous: INPUT_0 {
-                ki: KEYBDINPUT {
-                    wVk: key,
-                    wScan: 0,
-                    dwFlags: flags,
-                    time: 0,
-                    dwExtraInfo: 0,
-                },
-            },
+/// The strategy of typing characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TypingStrategy {
+    /// Types characters by the keys of the keyboard layout, including the shift state and dead keys.
+    /// The characters which can not be typed by the layout are typed as unicode.
+    #[default]
+    Layout,
+    /// Always types characters as unicode.
+    Unicode,
+    /// Types characters by the scan codes of the keyboard layout, for the applications which read hardware scan codes.
+    /// The characters which can not be typed by the layout are typed as unicode.
+    ScanCode
+}
+
+/// The mode of simulating input on an element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputMode {
+    /// Injects the input into the system by `SendInput()`, which needs the focus and moves the real cursor.
+    #[default]
+    Foreground,
+    /// Posts window messages to the native window of the element, which works in the background and on minimized windows.
+    /// See `MessageSink` for the limitations.
+    Background
+}
+
+/// Creates the input events of characters by a typing strategy.
+struct CharTyper<'a> {
+    strategy: TypingStrategy,
+    layout: &'a KeyboardLayout,
+    caps_lock: bool
+}
+
+impl<'a> CharTyper<'a> {
+    fn new(strategy: TypingStrategy, layout: &'a KeyboardLayout, caps_lock: bool) -> Self {
+        Self {
+            strategy,
+            layout,
+            caps_lock
         }
     }
 
-    fn create_char_key(ch: u16, hold_mode: bool) -> Vec<INPUT> {
-        // let code = ch as i32;
-        let vk: i16 = if ch < 256 {
-            unsafe { VkKeyScanW(ch) }
+    fn create_char_events(&self, ch: char, hold_mode: bool) -> Vec<InputEvent> {
+        let strokes = if self.strategy == TypingStrategy::Unicode {
+            None
         } else {
-            -1
+            self.layout.get_strokes(ch)
         };
-        
-        if vk == -1 {   // Unicode
-            vec![
-                INPUT {
-                    r#type: INPUT_KEYBOARD,
-                    Anonymous: INPUT_0 {
-                        ki: KEYBDINPUT {
-                            wVk: VIRTUAL_KEY(0),
-                            wScan: ch,
-                            dwFlags: KEYEVENTF_UNICODE,
-                            time: 0,
-                            dwExtraInfo: 0,
-                            },
-                        }
-                }, INPUT {
-                    r#type: INPUT_KEYBOARD,
-                    Anonymous: INPUT_0 {
-                        ki: KEYBDINPUT {
-                            wVk: VIRTUAL_KEY(0),
-                            wScan: ch,
-                            dwFlags: KEYEVENTF_UNICODE | KEYEVENTF_KEYUP,
-                            time: 0,
-                            dwExtraInfo: 0,
-                        },
-                    }
-                }
-            ]
-        } else {        // ASCII
-            let key: VIRTUAL_KEY = VIRTUAL_KEY((vk & 0xFF) as _);
-            if hold_mode {
-                vec![
-                    INPUT {
-                        r#type: INPUT_KEYBOARD,
-                        Anonymous: INPUT_0 {
-                            ki: KEYBDINPUT {
-                                wVk: key,
-                                wScan: 0,
-                                dwFlags: KEYEVENTF_KEYDOWN,
-                                time: 0,
-                                dwExtraInfo: 0,
-                                },
-                            }
-                    }, INPUT {
-                        r#type: INPUT_KEYBOARD,
-                        Anonymous: INPUT_0 {
-                            ki: KEYBDINPUT {
-                                wVk: key,
-                                wScan: 0,
-                                dwFlags: KEYEVENTF_KEYUP,
-                                time: 0,
-                                dwExtraInfo: 0,
-                            },
-                        }
-                    }
-                ]    
-            } else {
-                let mut shift: bool = (vk >> 8 & 0x01) != 0;
-                let state = unsafe { GetKeyState(VK_CAPITAL.0 as _) };
-                if (state & 0x01) != 0 {
-                    if (ch >= 'a' as u16 && ch <= 'z' as u16) || (ch >= 'A' as u16 && ch <= 'Z' as u16) {
-                        shift = !shift;
-                    }
-                };
-                let mut char_inputs: Vec<INPUT> = Vec::new();
-                if shift {
-                    char_inputs.push(
-                        INPUT {
-                            r#type: INPUT_KEYBOARD,
-                            Anonymous: INPUT_0 {
-                                ki: KEYBDINPUT {
-                                    wVk: VK_SHIFT,
-                                    wScan: 0,
-                                    dwFlags: KEYEVENTF_KEYDOWN,
-                                    time: 0,
-                                    dwExtraInfo: 0,
-                                    },
-                                }
-                        }
-                    );
-                }
-                char_inputs.push(
-                    INPUT {
-                        r#type: INPUT_KEYBOARD,
-                        Anonymous: INPUT_0 {
-                            ki: KEYBDINPUT {
-                                wVk: key,
-                                wScan: 0,
-                                dwFlags: KEYEVENTF_KEYDOWN,
-                                time: 0,
-                                dwExtraInfo: 0,
-                                },
-                            }
-                    }
-                );
-                char_inputs.push(INPUT {
-                        r#type: INPUT_KEYBOARD,
-                        Anonymous: INPUT_0 {
-                            ki: KEYBDINPUT {
-                                wVk: key,
-                                wScan: 0,
-                                dwFlags: KEYEVENTF_KEYUP,
-                                time: 0,
-                                dwExtraInfo: 0,
-                            },
-                        }
-                    }
-                );
-                if shift {
-                    char_inputs.push(
-                        INPUT {
-                            r#type: INPUT_KEYBOARD,
-                            Anonymous: INPUT_0 {
-                                ki: KEYBDINPUT {
-                                    wVk: VK_SHIFT,
-                                    wScan: 0,
-                                    dwFlags: KEYEVENTF_KEYUP,
-                                    time: 0,
-                                    dwExtraInfo: 0,
-                                    },
-                                }
-                        }
-                    );
-                };
-                char_inputs
-            }
+
+        if let Some(strokes) = strokes {
+            let last = strokes.len() - 1;
+            strokes.iter().enumerate().flat_map(|(i, stroke)| self.create_stroke_events(ch, stroke, hold_mode, i == last)).collect()
+        } else {
+            let mut buffer = [0; 2];
+            ch.encode_utf16(&mut buffer).iter().flat_map(|ch| [InputEvent::UnicodeDown(*ch), InputEvent::UnicodeUp(*ch)]).collect()
         }
     }
-}
 
-fn parse_input(expression: &str) -> Result<Vec<Input>> {
-    let mut inputs: Vec<Input> = Vec::new();
+    fn create_stroke_events(&self, ch: char, stroke: &KeyStroke, hold_mode: bool, is_last: bool) -> Vec<InputEvent> {
+        let key = self.key_code(stroke.get_key(), stroke.get_scan_code());
+        if hold_mode {
+            return vec![
+                InputEvent::KeyDown(key),
+                InputEvent::KeyUp(key)
+            ];
+        }
 
-    let mut expr = expression.chars();
-    while let Some((items, is_holdkey)) = next_input(&mut expr)? {
-        if let Some(prev) = inputs.last_mut() {
-            // if !is_holdkey && (prev.is_holdkey_only() || !prev.has_holdkey()) {
-            if (is_holdkey && !prev.has_items()) || (!is_holdkey && (!prev.has_holdkey() || prev.is_holdkey_only())) { 
-                prev.push_all(&items);
-                continue;
-            }
+        let mut modifiers = stroke.get_modifiers();
+        if self.caps_lock && is_last && ch.is_alphabetic() && (modifiers & (MODIFIER_CTRL | MODIFIER_ALT)) == 0 {
+            modifiers ^= MODIFIER_SHIFT;
         }
 
-        let mut input = Input::new();
-        input.push_all(&items);
+        let modifier_keys: Vec<KeyCode> = [(MODIFIER_SHIFT, VK_SHIFT, 0x2A), (MODIFIER_CTRL, VK_CONTROL, 0x1D), (MODIFIER_ALT, VK_MENU, 0x38)]
+            .iter()
+            .filter(|(modifier, _, _)| (modifiers & modifier) != 0)
+            .map(|(_, key, scan)| self.key_code(*key, *scan))
+            .collect();
+
+        let mut events: Vec<InputEvent> = modifier_keys.iter().map(|k| InputEvent::KeyDown(*k)).collect();
+        events.push(InputEvent::KeyDown(key));
+        events.push(InputEvent::KeyUp(key));
+        events.extend(modifier_keys.iter().rev().map(|k| InputEvent::KeyUp(*k)));
+        events
+    }
 
-        inputs.push(input);
+    fn key_code(&self, key: VIRTUAL_KEY, scan: u16) -> KeyCode {
+        if self.strategy == TypingStrategy::ScanCode && scan != 0 {
+            KeyCode::Scan(scan)
+        } else {
+            KeyCode::Virtual(key)
+        }
     }
+}
 
-    Ok(inputs)
+/// The code of a key which is sent to the system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCode {
+    /// A virtual key code, such as `VK_RETURN` or `{VK 0x5D}`.
+    Virtual(VIRTUAL_KEY),
+    /// A hardware scan code, such as `{SC 0x1C}`. Extended keys are prefixed by `0xE0`, such as `{SC 0xE05B}`. Key expressions reject other codes.
+    Scan(u16)
 }
 
-fn next_input(expr: &mut Chars<'_>) -> Result<Option<(Vec<InputItem>, bool)>> {
-    if let Some(ch) = expr.next() {
-        let next = match ch {
-            '{' => {
-                let item = read_special_item(expr)?;
-                Some((vec![item], item.is_holdkey()))
-            }
-            '(' => {
-                let items = read_group_items(expr)?;
-                Some((items, false))
-            }
-            _ => Some((vec![InputItem::Character(ch)], false)),
-        };
-        Ok(next)
-    } else {
-        Ok(None)
-    }
+/// A special key quoted by `{}` in a key expression, such as `{enter}` or `{ctrl}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecialKey {
+    name: String,
+    code: KeyCode,
+    count: usize
 }
 
-fn read_special_item(expr: &mut Chars<'_>) -> Result<InputItem> {
-    let mut token = String::new();
-    let mut matched = false;
-    while let Some(ch) = expr.next() {
-        if ch == '}' && !token.is_empty() {
-            matched = true;
-            break;
-        } else {
-            token.push(ch);
+impl SpecialKey {
+    pub(crate) fn new(name: String, code: KeyCode, count: usize) -> Self {
+        Self {
+            name,
+            code,
+            count
         }
     }
 
-    if matched {
-        if token == "(" || token == ")" || token == "{" || token == "}" {
-            Ok(InputItem::Character(token.chars().nth(0).unwrap()))
-        } else {
-            let token = token.to_uppercase();
-            if let Some(key) = VIRTUAL_KEYS.get(&token) {
-                if HOLD_KEYS.contains(&token) {
-                    Ok(InputItem::HoldKey(*key))
-                } else {
-                    Ok(InputItem::VirtualKey(*key))
-                }
-            } else {
-                Err(Error::new(ERR_FORMAT, "Error Input Format"))
-            }
-        }
-    } else {
-        Err(Error::new(ERR_FORMAT, "Error Input Format"))
+    /// Retrieves the uppercase name of the key, such as `ENTER` or `VK 0x5D`.
+    pub fn get_name(&self) -> &str {
+        self.name.as_str()
     }
-}
 
-fn read_group_items(expr: &mut Chars<'_>) -> Result<Vec<InputItem>> {
-    let mut items: Vec<InputItem> = Vec::new();
-    let mut matched = false;
-
-    while let Some((next, _)) = next_input(expr)? {
-        if next.len() == 1 && next[0] == InputItem::Character(')') {
-            matched = true;
-            break;
+    /// Retrieves the virtual key code of the key. `VIRTUAL_KEY(0)` is returned for a scan code key.
+    pub fn get_key(&self) -> VIRTUAL_KEY {
+        match self.code {
+            KeyCode::Virtual(key) => key,
+            KeyCode::Scan(_) => VIRTUAL_KEY(0)
         }
+    }
 
-        items.extend(next);
+    /// Retrieves the key code of the key.
+    pub fn get_code(&self) -> KeyCode {
+        self.code
     }
 
-    if matched {
-        Ok(items)
-    } else {
-        Err(Error::new(ERR_FORMAT, "Error Input Format"))
+    /// Retrieves the repeat count of the key, such as `5` for `{TAB 5}`. The default count is `1`.
+    pub fn get_count(&self) -> usize {
+        self.count
     }
 }
 
-/// Simulate typing keys on keyboard.
-#[derive(Debug, Default)]
-pub struct Keyboard {
-    interval: u64
+impl Display for SpecialKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.count == 1 {
+            write!(f, "{{{}}}", self.name)
+        } else {
+            write!(f, "{{{} {}}}", self.name, self.count)
+        }
+    }
 }
 
-impl Keyboard {
-    /// Create a keyboard to simulate typing keys.
-    pub fn new() -> Self {
-        Self {
-            interval: 0
+/// An item of a key expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyItem {
+    /// A run of literal characters, such as `Hello`. Quoted characters like `{(}` are included in the text.
+    Text(String),
+    /// A special key which is typed once, such as `{enter}`.
+    Key(SpecialKey),
+    /// A hold key which is held down while the next character or group is typed, such as `{ctrl}`.
+    HoldKey(SpecialKey),
+    /// Presses a key down until the matching `KeyUp` or the end of the typing, such as `{shift down}`.
+    KeyDown(SpecialKey),
+    /// Releases a key pressed by `KeyDown`, such as `{shift up}`.
+    KeyUp(SpecialKey),
+    /// A group of items quoted by `()`, such as `(ab)`.
+    Group(Vec<KeyItem>)
+}
+
+impl Display for KeyItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text(text) => {
+                for ch in text.chars() {
+                    match ch {
+                        '{' | '}' | '(' | ')' => write!(f, "{{{}}}", ch)?,
+                        _ => write!(f, "{}", ch)?
+                    }
+                }
+                Ok(())
+            },
+            Self::Key(key) | Self::HoldKey(key) => write!(f, "{}", key),
+            Self::KeyDown(key) => write!(f, "{{{} down}}", key.get_name()),
+            Self::KeyUp(key) => write!(f, "{{{} up}}", key.get_name()),
+            Self::Group(items) => {
+                write!(f, "(")?;
+                for item in items {
+                    write!(f, "{}", item)?;
+                }
+                write!(f, ")")
+            }
         }
     }
+}
 
-    /// Set the interval time between keys.
-    /// 
-    /// `interval` is the time number of milliseconds, `0` is default value.
-    pub fn interval(mut self, interval: u64) -> Self {
-        self.interval = interval;
-        self
-    }
+/// The syntax tree of a key expression used by `Keyboard::send_keys()`.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use uiautomation::inputs::KeyExpression;
+/// use uiautomation::inputs::KeyItem;
+/// 
+/// let expr = KeyExpression::parse("{ctrl}(ab){enter}").unwrap();
+/// assert_eq!(expr.get_items().len(), 3);
+/// assert!(matches!(expr.get_items()[1], KeyItem::Group(_)));
+/// assert_eq!(expr.to_string(), "{CTRL}(ab){ENTER}");
+/// 
+/// let err = KeyExpression::parse("Hello{Entr}").unwrap_err();
+/// assert_eq!(err.get_offset(), 5);
+/// assert_eq!(err.get_token(), "{Entr}");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct KeyExpression {
+    items: Vec<KeyItem>
+}
 
-    /// Simulates typing `keys` on keyboard.
-    /// 
-    /// `{}` is used for some special keys. For example: `{ctrl}{alt}{delete}`, `{shift}{home}`.
+impl KeyExpression {
+    /// Parses a key expression.
     /// 
-    /// `()` is used for group keys. For example: `{ctrl}(AB)` types `Ctrl+A+B`.
-    /// 
-    /// `{` `}` `(` `)` can be quoted by `{}`. For example: `{{}Hi,{(}rust!{)}{}}` types `{Hi,(rust)}`.
-    pub fn send_keys(&self, keys: &str) -> Result<()> {
-        let inputs = parse_input(keys)?;
-        for ref input in inputs {
-            self.send_keyboard(input)?;
-        }
+    /// The error reports the character offset and the offending token.
+    pub fn parse(expression: &str) -> core::result::Result<Self, KeyParseError> {
+        let mut parser = Parser::new(expression);
+        let items = parser.read_items(None)?;
+
+        Ok(Self {
+            items
+        })
+    }
 
-        Ok(())
+    /// Retrieves the items of the expression.
+    pub fn get_items(&self) -> &[KeyItem] {
+        self.items.as_slice()
     }
+}
 
-    fn send_keyboard(&self, input: &Input) -> Result<()> {
-        let input_keys = input.create_inputs()?;
-        if self.interval == 0 {
-            send_input(&input_keys.as_slice())
-        } else {
-            for input_key in &input_keys {
-                let input_key_slice: [INPUT; 1] = [input_key.clone()];
-                send_input(&input_key_slice)?;
+impl From<Vec<KeyItem>> for KeyExpression {
+    fn from(items: Vec<KeyItem>) -> Self {
+        Self {
+            items
+        }
+    }
+}
 
-                self.wait();
-            }
+impl FromStr for KeyExpression {
+    type Err = KeyParseError;
 
-            Ok(())
-        }
+    fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+        Self::parse(s)
     }
+}
 
-    fn wait(&self) {
-        if self.interval > 0 {
-            sleep(Duration::from_millis(self.interval));
+impl Display for KeyExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for item in &self.items {
+            write!(f, "{}", item)?;
         }
+        Ok(())
     }
 }
 
-/// Simulate mouse event.
-#[derive(Debug)]
-pub struct Mouse {
-    interval: u64,
-    move_time: u64,
-    auto_move: bool,
-    holdkeys: Vec<VIRTUAL_KEY>
+/// The error occurs when a key expression can not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyParseError {
+    offset: usize,
+    token: String,
+    message: String
 }
 
-impl Default for Mouse {
-    fn default() -> Self {
-        Self { 
-            interval: 100, 
-            move_time: 500,
-            auto_move: true,
-            holdkeys: Vec::new()
+impl KeyParseError {
+    pub(crate) fn new(offset: usize, token: &str, message: &str) -> Self {
+        Self {
+            offset,
+            token: String::from(token),
+            message: String::from(message)
         }
     }
-}
 
-impl Mouse {
-    /// Creates a `Mouse` to simulate mouse event.
-    pub fn new() -> Self {
-        Self::default()
+    /// Retrieves the character offset of the offending token in the expression.
+    pub fn get_offset(&self) -> usize {
+        self.offset
     }
 
-    /// Sets the interval time between events.
-    /// 
-    /// `interval` is the time number of milliseconds, `100` is default value.
-    pub fn interval(mut self, interval: u64) -> Self {
-        self.interval = interval;
-        self
+    /// Retrieves the offending token.
+    pub fn get_token(&self) -> &str {
+        self.token.as_str()
     }
 
-    /// Sets the mouse move time in millionseconds. `500` is default value.
-    pub fn move_time(mut self, move_time: u64) -> Self {
-        self.move_time = move_time;
-        self
+    /// Retrieves the reason of the error.
+    pub fn get_message(&self) -> &str {
+        self.message.as_str()
     }
+}
 
-    /// Sets whether move the cursor to the click point automatically. Default is `true`.
-    pub fn auto_move(mut self, auto_move: bool) -> Self {
-        self.auto_move = auto_move;
-        self
+impl Display for KeyParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} `{}` at offset {}", self.message, self.token, self.offset)
     }
+}
 
-    /// Sets the holdkeys when mouse clicks.
-    /// 
-    /// The holdkeys is quoted by `{}`. For example: `{Shift}`, `{Ctrl}{Alt}`.
-    pub fn holdkeys(mut self, holdkeys: &str) -> Self {
-        self.holdkeys.clear();
+impl std::error::Error for KeyParseError {
+}
 
-        let mut expr = holdkeys.chars();
-        while let Some((items, is_holdkey)) = next_input(&mut expr).unwrap() {
-            if is_holdkey {
-                for item in items {
-                    if let InputItem::HoldKey(key) = item {
-                        self.holdkeys.push(key);
-                    }
-                }
-            }
-        }
-            
-        self
+impl From<KeyParseError> for Error {
+    fn from(e: KeyParseError) -> Self {
+        Error::new(ERR_FORMAT, &e.to_string())
     }
+}
 
-    /// Retrieves the position of the mouse cursor, in screen coordinates.
-    pub fn get_cursor_pos() -> Result<Point> {
-        let mut pos: Point = Point::default();
-        let ret = unsafe {
-            GetCursorPos(pos.as_mut())
-        };
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    pressed: Vec<KeyCode>
+}
 
-        if ret.as_bool() {
-            Ok(pos)
-        } else {
-            Err(Error::last_os_error())
+impl Parser {
+    fn new(expression: &str) -> Self {
+        Self {
+            chars: expression.chars().collect(),
+            pos: 0,
+            pressed: Vec::new()
         }
     }
 
-    /// Moves the cursor to the specified screen coordinates. 
-    pub fn set_cursor_pos(pos: Point) -> Result<()> {
-        let ret = unsafe { SetCursorPos(pos.get_x(), pos.get_y()) };
-        if ret.as_bool() {
-            Ok(())
-        } else {
-            Err(Error::last_os_error())
-        }
+    fn token(&self, start: usize) -> String {
+        self.chars[start..self.pos].iter().collect()
     }
 
-    /// Moves the cursor from current position to the `target` position.
-    /// 
-    /// # Examples
-    /// 
-    /// ```
-    /// use uiautomation::inputs::Mouse;
-    /// use uiautomation::types::Point;
-    /// 
-    /// let mouse = Mouse::new().move_time(800);
-    /// mouse.move_to(Point::new(10, 20)).unwrap();
-    /// mouse.move_to(Point::new(1000,800)).unwrap();
-    /// ```
-    pub fn move_to(&self, target: Point) -> Result<()> {
-        let (width, height) = get_screen_size()?;
-        let x = min(max(0, target.get_x()), width);
-        let y = min(max(0, target.get_y()), height);
-        let target = Point::new(x, y);
-        
-        if self.move_time > 0 {
-            let source = Self::get_cursor_pos()?;
-            let delta_x = target.get_x() - source.get_x();
-            let delta_y = target.get_y() - source.get_y();
+    fn read_items(&mut self, group_start: Option<usize>) -> core::result::Result<Vec<KeyItem>, KeyParseError> {
+        let mut items: Vec<KeyItem> = Vec::new();
 
-            let delta = max(delta_x.abs(), delta_y.abs());
-            let steps = delta / 20;
-            if steps > 1 {
-                let step_x = delta_x / steps;
-                let step_y = delta_y / steps;
-                let interval = Duration::from_millis(self.move_time / steps as u64);
-                for i in 1..steps {
-                    let pos = Point::new(
-                        source.get_x() + step_x * i, 
-                        source.get_y() + step_y * i
-                    );
-                    Self::set_cursor_pos(pos)?;
-                    sleep(interval);
+        while let Some(&ch) = self.chars.get(self.pos) {
+            match ch {
+                '{' => {
+                    let item = self.read_special_item()?;
+                    push_item(&mut items, item);
+                },
+                '(' => {
+                    let item = self.read_group_items()?;
+                    items.push(item);
+                },
+                ')' if group_start.is_some() => {
+                    self.pos += 1;
+                    return Ok(items);
+                },
+                _ => {
+                    self.pos += 1;
+                    push_item(&mut items, KeyItem::Text(ch.to_string()));
                 }
             }
         }
 
-        Self::set_cursor_pos(target)
+        if let Some(start) = group_start {
+            Err(KeyParseError::new(start, "(", "Unclosed group"))
+        } else {
+            Ok(items)
+        }
     }
 
-    /// Simulates a mouse click event.
-    /// 
-    /// # Examples
-    /// ```
-    /// use uiautomation::inputs::Mouse;
-    /// 
-    /// let mouse = Mouse::new();
-    /// let pos = Mouse::get_cursor_pos().unwrap();
-    /// mouse.click(pos).unwrap();
-    /// ```
-    pub fn click(&self, pos: Point) -> Result<()> {
-        if self.auto_move {
-            self.move_to(pos)?;
+    fn read_special_item(&mut self) -> core::result::Result<KeyItem, KeyParseError> {
+        let start = self.pos;
+        self.pos += 1;
+
+        let mut token = String::new();
+        let mut matched = false;
+        while let Some(&ch) = self.chars.get(self.pos) {
+            self.pos += 1;
+            if ch == '}' && !token.is_empty() {
+                matched = true;
+                break;
+            } else {
+                token.push(ch);
+            }
         }
 
-        self.before_click()?;
-        self.mouse_event(pos.get_x(), pos.get_y(), MOUSEEVENTF_LEFTDOWN)?;
-        self.mouse_event(pos.get_x(), pos.get_y(), MOUSEEVENTF_LEFTUP)?;
-        self.after_click()?;
+        if !matched {
+            return Err(KeyParseError::new(start, &self.token(start), "Unterminated special key"));
+        }
 
-        Ok(())
+        if let Some((name, down)) = split_key_state(&token) {
+            return self.read_key_state(start, name, down);
+        }
+
+        let (token, count) = match split_repeat_count(&token) {
+            Some((name, _)) if is_raw_code_prefix(name) => (token.as_str(), 1),
+            Some((_, Some(count))) if count > MAX_REPEAT_COUNT => return Err(KeyParseError::new(start, &self.token(start), "Repeat count too large")),
+            Some((name, Some(count))) if count > 0 => (name, count),
+            Some(_) => return Err(KeyParseError::new(start, &self.token(start), "Invalid repeat count")),
+            None => (token.as_str(), 1)
+        };
+
+        if token == "(" || token == ")" || token == "{" || token == "}" {
+            Ok(KeyItem::Text(token.repeat(count)))
+        } else if let Some((name, code)) = lookup_key(token) {
+            let key = SpecialKey {
+                name,
+                code,
+                count
+            };
+            if HOLD_KEYS.contains(key.get_name()) {
+                if count == 1 {
+                    Ok(KeyItem::HoldKey(key))
+                } else {
+                    Err(KeyParseError::new(start, &self.token(start), "Hold key can not be repeated"))
+                }
+            } else {
+                Ok(KeyItem::Key(key))
+            }
+        } else {
+            Err(KeyParseError::new(start, &self.token(start), "Unknown key"))
+        }
+    }
+
+    fn read_key_state(&mut self, start: usize, name: &str, down: bool) -> core::result::Result<KeyItem, KeyParseError> {
+        let key = match lookup_key(name) {
+            Some((name, code)) => SpecialKey {
+                name,
+                code,
+                count: 1
+            },
+            None => return Err(KeyParseError::new(start, &self.token(start), "Unknown key"))
+        };
+
+        let pressed = self.pressed.contains(&key.get_code());
+        if down {
+            if pressed {
+                return Err(KeyParseError::new(start, &self.token(start), "Key is already down"));
+            }
+            self.pressed.push(key.get_code());
+            Ok(KeyItem::KeyDown(key))
+        } else {
+            if !pressed {
+                return Err(KeyParseError::new(start, &self.token(start), "Key is not down"));
+            }
+            self.pressed.retain(|k| *k != key.get_code());
+            Ok(KeyItem::KeyUp(key))
+        }
+    }
+
+    fn read_group_items(&mut self) -> core::result::Result<KeyItem, KeyParseError> {
+        let start = self.pos;
+        self.pos += 1;
+
+        let items = self.read_items(Some(start))?;
+        Ok(KeyItem::Group(items))
+    }
+}
+
+/// Looks up a key by its name or its raw code, such as `enter`, `VK 0x5D` or `SC 28`.
+/// 
+/// Returns the canonical name and the key code.
+pub(crate) fn lookup_key(token: &str) -> Option<(String, KeyCode)> {
+    let name = token.to_uppercase();
+    if let Some(key) = VIRTUAL_KEYS.get(&name) {
+        return Some((name, KeyCode::Virtual(*key)));
+    }
+
+    let (prefix, code) = name.split_once(' ')?;
+    let code = code.trim();
+    let code = if let Some(hex) = code.strip_prefix("0X") {
+        u16::from_str_radix(hex, 16).ok()?
+    } else {
+        code.parse::<u16>().ok()?
+    };
+    match prefix {
+        "VK" if code > 0 && code < 0xFF => Some((format!("VK 0x{:02X}", code), KeyCode::Virtual(VIRTUAL_KEY(code)))),
+        "SC" if is_valid_scan_code(code) => Some((format!("SC 0x{:02X}", code), KeyCode::Scan(code))),
+        _ => None
+    }
+}
+
+/// Determines whether the scan code can be sent, which is one byte or one byte prefixed by `0xE0`.
+fn is_valid_scan_code(code: u16) -> bool {
+    matches!(code, 0x01..=0xFF | 0xE001..=0xE0FF)
+}
+
+fn is_raw_code_prefix(name: &str) -> bool {
+    name.eq_ignore_ascii_case("VK") || name.eq_ignore_ascii_case("SC")
+}
+
+/// Retrieves the virtual key code by the key name used in key expressions, such as `enter`, `NUMPAD0` or `VK 0x5D`.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use uiautomation::inputs::get_virtual_key;
+/// use windows::Win32::UI::Input::KeyboardAndMouse::VK_RETURN;
+/// 
+/// assert_eq!(get_virtual_key("Enter"), Some(VK_RETURN));
+/// assert_eq!(get_virtual_key("Entr"), None);
+/// ```
+pub fn get_virtual_key(name: &str) -> Option<VIRTUAL_KEY> {
+    match lookup_key(name) {
+        Some((_, KeyCode::Virtual(key))) => Some(key),
+        _ => None
+    }
+}
+
+/// Retrieves all the supported key names in key expressions, in alphabetical order.
+pub fn get_key_names() -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = VIRTUAL_KEYS.keys().copied().collect();
+    names.sort_unstable();
+    names
+}
+
+/// Indicates whether the key name is a hold key, such as `ctrl`, `alt`, `shift` or `win`.
+pub fn is_hold_key(name: &str) -> bool {
+    HOLD_KEYS.contains(name.to_uppercase().as_str())
+}
+
+/// Escapes the `text` into a key expression which types the text literally, quoting `{` `}` `(` `)` by `{}`.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use uiautomation::inputs::escape_keys;
+/// 
+/// assert_eq!(escape_keys("{Hi,(rust)}"), "{{}Hi,{(}rust{)}{}}");
+/// ```
+pub fn escape_keys(text: &str) -> String {
+    KeyItem::Text(String::from(text)).to_string()
+}
+
+/// Splits a key state token such as `SHIFT down` or `SHIFT up` into the key name and whether the key is pressed down.
+fn split_key_state(token: &str) -> Option<(&str, bool)> {
+    let (name, state) = token.rsplit_once(' ')?;
+    if name.is_empty() {
+        None
+    } else if state.eq_ignore_ascii_case("down") {
+        Some((name, true))
+    } else if state.eq_ignore_ascii_case("up") {
+        Some((name, false))
+    } else {
+        None
+    }
+}
+
+/// The maximum repeat count of a key, such as `{TAB 1000}`.
+pub const MAX_REPEAT_COUNT: usize = 1000;
+
+/// Splits a repeated token such as `TAB 5` or `DOWN*15` into the key name and the repeat count.
+/// 
+/// Returns `None` if the token has no repeat count, or `Some((name, None))` if the count is not a valid number.
+fn split_repeat_count(token: &str) -> Option<(&str, Option<usize>)> {
+    let (index, _) = token.char_indices().skip(1).filter(|(_, ch)| *ch == ' ' || *ch == '*').last()?;
+    let name = &token[..index];
+    let count = &token[index + 1..];
+    if count.is_empty() || !count.chars().all(|ch| ch.is_ascii_digit()) {
+        None
+    } else {
+        Some((name, count.parse().ok()))
+    }
+}
+
+/// Appends `item` to `items`, merging adjacent text runs.
+fn push_item(items: &mut Vec<KeyItem>, item: KeyItem) {
+    if let KeyItem::Text(ref text) = item {
+        if let Some(KeyItem::Text(prev)) = items.last_mut() {
+            prev.push_str(text);
+            return;
+        }
+    }
+
+    items.push(item);
+}
+
+fn parse_input(expression: &str) -> Result<Vec<Input>> {
+    let expression = KeyExpression::parse(expression)?;
+    Ok(create_inputs(&expression))
+}
+
+fn create_inputs(expression: &KeyExpression) -> Vec<Input> {
+    let mut inputs: Vec<Input> = Vec::new();
+
+    for (items, is_holdkey) in next_inputs(expression.get_items()) {
+        let is_key_state = items.len() == 1 && items[0].is_key_state();
+        if let Some(prev) = inputs.last_mut() {
+            // if !is_holdkey && (prev.is_holdkey_only() || !prev.has_holdkey()) {
+            let merged = if is_holdkey {
+                !prev.has_items()
+            } else if is_key_state {
+                !prev.has_holdkey()
+            } else {
+                !prev.has_holdkey() || prev.is_holdkey_only()
+            };
+            if merged {
+                prev.push_all(&items);
+                continue;
+            }
+        }
+
+        let mut input = Input::new();
+        input.push_all(&items);
+
+        inputs.push(input);
+    }
+
+    inputs
+}
+
+/// Splits the expression items into input units. Each unit is a character, a (repeated) special key, a hold key or a group.
+fn next_inputs(items: &[KeyItem]) -> Vec<(Vec<InputItem>, bool)> {
+    let mut inputs: Vec<(Vec<InputItem>, bool)> = Vec::new();
+
+    for item in items {
+        match item {
+            KeyItem::Text(text) => {
+                for ch in text.chars() {
+                    inputs.push((vec![InputItem::Character(ch)], false));
+                }
+            },
+            KeyItem::Key(key) => {
+                let item = match key.get_code() {
+                    KeyCode::Virtual(key) => InputItem::VirtualKey(key),
+                    KeyCode::Scan(code) => InputItem::ScanCode(code)
+                };
+                inputs.push((vec![item; key.get_count()], false));
+            },
+            KeyItem::HoldKey(key) => inputs.push((vec![InputItem::HoldKey(key.get_key())], true)),
+            KeyItem::KeyDown(key) => inputs.push((vec![InputItem::KeyDown(key.get_code())], false)),
+            KeyItem::KeyUp(key) => inputs.push((vec![InputItem::KeyUp(key.get_code())], false)),
+            KeyItem::Group(group) => {
+                let group_items: Vec<InputItem> = next_inputs(group).into_iter().flat_map(|(items, _)| items).collect();
+                inputs.push((group_items, false));
+            }
+        }
+    }
+
+    inputs
+}
+
+/// The buttons of the mouse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MouseButton {
+    /// The left button.
+    Left,
+    /// The right button.
+    Right,
+    /// The middle button (the wheel).
+    Middle,
+    /// The first extended button, usually the `back` button.
+    X1,
+    /// The second extended button, usually the `forward` button.
+    X2
+}
+
+/// A platform-neutral input event which is sent to an `InputSink`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputEvent {
+    /// Presses a key.
+    KeyDown(KeyCode),
+    /// Releases a key.
+    KeyUp(KeyCode),
+    /// Presses a UTF-16 code unit which is typed as unicode.
+    UnicodeDown(u16),
+    /// Releases a UTF-16 code unit which is typed as unicode.
+    UnicodeUp(u16),
+    /// Moves the cursor to the screen position.
+    Move(Point),
+    /// Moves the cursor to the virtual-desktop position by an absolute mouse event.
+    MoveAbsolute(Point),
+    /// Presses a mouse button.
+    ButtonDown(MouseButton),
+    /// Releases a mouse button.
+    ButtonUp(MouseButton),
+    /// Rotates the vertical wheel. `120` is one wheel tick, a positive value rotates forward, away from the user.
+    Wheel(i32),
+    /// Rotates the horizontal wheel. `120` is one wheel tick, a positive value rotates to the right.
+    HorizontalWheel(i32)
+}
+
+/// The target which receives the input events of `Keyboard` and `Mouse`.
+/// 
+/// `SendInputSink` injects the events into the system. `RecordingSink` only records them, 
+/// so the simulated input can be verified on any platform.
+pub trait InputSink {
+    /// Sends the `events` in order.
+    fn send(&self, events: &[InputEvent]) -> Result<()>;
+
+    /// Retrieves the position of the mouse cursor, in screen coordinates.
+    fn get_cursor_pos(&self) -> Result<Point>;
+
+    /// Retrieves the `(width, height)` size of the screen.
+    fn get_screen_size(&self) -> Result<(i32, i32)>;
+
+    /// Retrieves the bounds of the monitors, in virtual-desktop coordinates.
+    /// 
+    /// The default implementation returns the screen of `get_screen_size()`.
+    fn get_monitors(&self) -> Result<Vec<Rect>> {
+        let (width, height) = self.get_screen_size()?;
+        Ok(vec![Rect::new(0, 0, width, height)])
+    }
+
+    /// Retrieves the keyboard layout which is used to type characters.
+    fn get_keyboard_layout(&self) -> KeyboardLayout;
+
+    /// Determines whether the toggle `key`, such as `VK_CAPITAL`, is on.
+    fn is_key_toggled(&self, key: VIRTUAL_KEY) -> bool;
+
+    /// Determines whether the `key` is pressed down. The default implementation returns `false`.
+    fn is_key_pressed(&self, _key: VIRTUAL_KEY) -> bool {
+        false
+    }
+}
+
+impl<T: InputSink + ?Sized> InputSink for &T {
+    fn send(&self, events: &[InputEvent]) -> Result<()> {
+        (**self).send(events)
+    }
+
+    fn get_cursor_pos(&self) -> Result<Point> {
+        (**self).get_cursor_pos()
+    }
+
+    fn get_screen_size(&self) -> Result<(i32, i32)> {
+        (**self).get_screen_size()
+    }
+
+    fn get_monitors(&self) -> Result<Vec<Rect>> {
+        (**self).get_monitors()
+    }
+
+    fn get_keyboard_layout(&self) -> KeyboardLayout {
+        (**self).get_keyboard_layout()
+    }
+
+    fn is_key_toggled(&self, key: VIRTUAL_KEY) -> bool {
+        (**self).is_key_toggled(key)
+    }
+
+    fn is_key_pressed(&self, key: VIRTUAL_KEY) -> bool {
+        (**self).is_key_pressed(key)
+    }
+}
+
+/// An `InputSink` which injects the events into the system by `SendInput()`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SendInputSink;
+
+impl SendInputSink {
+    fn create_input(event: &InputEvent) -> Option<INPUT> {
+        let input = match *event {
+            InputEvent::KeyDown(key) => Self::create_key_input(key, KEYEVENTF_KEYDOWN),
+            InputEvent::KeyUp(key) => Self::create_key_input(key, KEYEVENTF_KEYUP),
+            InputEvent::UnicodeDown(ch) => Self::create_keyboard_input(VIRTUAL_KEY(0), ch, KEYEVENTF_UNICODE),
+            InputEvent::UnicodeUp(ch) => Self::create_keyboard_input(VIRTUAL_KEY(0), ch, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP),
+            InputEvent::Move(_) | InputEvent::MoveAbsolute(_) => return None,
+            InputEvent::ButtonDown(button) => Self::create_button_input(button, true),
+            InputEvent::ButtonUp(button) => Self::create_button_input(button, false),
+            InputEvent::Wheel(delta) => Self::create_mouse_input(delta, MOUSEEVENTF_WHEEL),
+            InputEvent::HorizontalWheel(delta) => Self::create_mouse_input(delta, MOUSEEVENTF_HWHEEL),
+        };
+
+        Some(input)
+    }
+
+    fn create_key_input(code: KeyCode, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+        match code {
+            KeyCode::Virtual(key) => Self::create_keyboard_input(key, 0, flags),
+            KeyCode::Scan(scan) => {
+                let flags = if scan >> 8 == 0xE0 {
+                    flags | KEYEVENTF_SCANCODE | KEYEVENTF_EXTENDEDKEY
+                } else {
+                    flags | KEYEVENTF_SCANCODE
+                };
+                Self::create_keyboard_input(VIRTUAL_KEY(0), scan & 0xFF, flags)
+            }
+        }
+    }
+
+    fn create_keyboard_input(key: VIRTUAL_KEY, scan: u16, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+        INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: key,
+                    wScan: scan,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        }
+    }
+
+    fn create_button_input(button: MouseButton, down: bool) -> INPUT {
+        let (data, flags) = match (button, down) {
+            (MouseButton::Left, true) => (0, MOUSEEVENTF_LEFTDOWN),
+            (MouseButton::Left, false) => (0, MOUSEEVENTF_LEFTUP),
+            (MouseButton::Right, true) => (0, MOUSEEVENTF_RIGHTDOWN),
+            (MouseButton::Right, false) => (0, MOUSEEVENTF_RIGHTUP),
+            (MouseButton::Middle, true) => (0, MOUSEEVENTF_MIDDLEDOWN),
+            (MouseButton::Middle, false) => (0, MOUSEEVENTF_MIDDLEUP),
+            (MouseButton::X1, true) => (XBUTTON1.0 as i32, MOUSEEVENTF_XDOWN),
+            (MouseButton::X1, false) => (XBUTTON1.0 as i32, MOUSEEVENTF_XUP),
+            (MouseButton::X2, true) => (XBUTTON2.0 as i32, MOUSEEVENTF_XDOWN),
+            (MouseButton::X2, false) => (XBUTTON2.0 as i32, MOUSEEVENTF_XUP),
+        };
+        Self::create_mouse_input(data, flags)
+    }
+
+    fn create_mouse_input(data: i32, flags: MOUSE_EVENT_FLAGS) -> INPUT {
+        INPUT {
+            r#type: INPUT_MOUSE,
+            Anonymous: INPUT_0 { 
+                mi: MOUSEINPUT { 
+                    dx: 0, 
+                    dy: 0, 
+                    mouseData: data, 
+                    dwFlags: flags, 
+                    time: 0, 
+                    dwExtraInfo: 0 
+                }
+            }
+        }
+    }
+}
+
+impl InputSink for SendInputSink {
+    fn send(&self, events: &[InputEvent]) -> Result<()> {
+        let mut inputs: Vec<INPUT> = Vec::new();
+        let mut virtual_screen: Option<Rect> = None;
+        for event in events {
+            if let Some(input) = Self::create_input(event) {
+                inputs.push(input);
+            } else if let InputEvent::Move(pos) = event {
+                if !inputs.is_empty() {
+                    send_input(&inputs)?;
+                    inputs.clear();
+                }
+
+                let ret = unsafe { SetCursorPos(pos.get_x(), pos.get_y()) };
+                if !ret.as_bool() {
+                    return Err(Error::last_os_error());
+                }
+            } else if let InputEvent::MoveAbsolute(pos) = event {
+                if virtual_screen.is_none() {
+                    virtual_screen = Some(get_virtual_screen()?);
+                }
+
+                let (x, y) = to_absolute_coordinates(*pos, virtual_screen.unwrap());
+                let mut input = Self::create_mouse_input(0, MOUSEEVENTF_MOVE | MOUSEEVENTF_ABSOLUTE | MOUSEEVENTF_VIRTUALDESK);
+                input.Anonymous.mi.dx = x;
+                input.Anonymous.mi.dy = y;
+                inputs.push(input);
+            }
+        }
+
+        if inputs.is_empty() {
+            Ok(())
+        } else {
+            send_input(&inputs)
+        }
+    }
+
+    fn get_cursor_pos(&self) -> Result<Point> {
+        let mut pos: Point = Point::default();
+        let ret = unsafe {
+            GetCursorPos(pos.as_mut())
+        };
+
+        if ret.as_bool() {
+            Ok(pos)
+        } else {
+            Err(Error::last_os_error())
+        }
+    }
+
+    fn get_screen_size(&self) -> Result<(i32, i32)> {
+        get_screen_size()
+    }
+
+    fn get_monitors(&self) -> Result<Vec<Rect>> {
+        let monitors = Monitor::get_monitors()?;
+        Ok(monitors.iter().map(|m| m.get_bounds()).collect())
+    }
+
+    fn get_keyboard_layout(&self) -> KeyboardLayout {
+        KeyboardLayout::current()
+    }
+
+    fn is_key_toggled(&self, key: VIRTUAL_KEY) -> bool {
+        let state = unsafe { GetKeyState(key.0 as _) };
+        (state & 0x01) != 0
+    }
+
+    fn is_key_pressed(&self, key: VIRTUAL_KEY) -> bool {
+        let state = unsafe { GetAsyncKeyState(key.0 as _) };
+        (state as u16 & 0x8000) != 0
+    }
+}
+
+/// An `InputSink` which posts window messages to a window instead of injecting the events into the system.
+/// 
+/// It works without the focus and the real cursor, so the automation can run in the background and on minimized windows.
+/// Keys are posted as `WM_KEYDOWN`/`WM_KEYUP` (`WM_SYSKEYDOWN`/`WM_SYSKEYUP` with alt), characters as `WM_CHAR`,
+/// and mouse events as `WM_MOUSEMOVE`, `WM_LBUTTONDOWN` and so on in client coordinates of the window.
+/// 
+/// The limitations are:
+/// 
+/// + The keyboard state of the system is not changed, so the applications which read `GetKeyState()` do not see the hold keys,
+///   and shortcuts such as `{ctrl}s` or `{alt}f` usually do not work.
+/// + Characters are posted directly, so input methods and dead keys are bypassed.
+/// + The messages are posted to a single window. A control without its own window handle, such as the content of a browser,
+///   receives the messages of its host window at the client coordinates.
+/// + Drag and drop which depends on mouse capture or OLE does not work.
+/// + Some frameworks and games ignore posted input messages, and windows of a process with higher integrity level can not be posted.
+/// 
+/// # Examples
+/// 
+/// ```no_run
+/// use uiautomation::core::UIAutomation;
+/// use uiautomation::inputs::Keyboard;
+/// use uiautomation::inputs::MessageSink;
+/// 
+/// let automation = UIAutomation::new().unwrap();
+/// let notepad = automation.create_matcher().classname("Edit").timeout(1000).find_first().unwrap();
+/// let keyboard = Keyboard::with_sink(MessageSink::new(notepad.get_native_window_handle().unwrap()));
+/// keyboard.send_keys("Hello{enter}").unwrap();
+/// ```
+#[derive(Debug)]
+pub struct MessageSink {
+    handle: Handle,
+    cursor: Cell<Point>,
+    keys: RefCell<Vec<VIRTUAL_KEY>>,
+    buttons: Cell<u32>,
+    last_click: Cell<Option<(MouseButton, Point, Instant)>>
+}
+
+impl MessageSink {
+    /// Creates a sink which posts the messages to the window of the `handle`.
+    pub fn new(handle: Handle) -> Self {
+        Self {
+            handle,
+            cursor: Cell::new(Point::default()),
+            keys: RefCell::new(Vec::new()),
+            buttons: Cell::new(0),
+            last_click: Cell::new(None)
+        }
+    }
+
+    /// Retrieves the handle of the target window.
+    pub fn get_handle(&self) -> Handle {
+        self.handle
+    }
+
+    fn post(&self, msg: u32, wparam: usize, lparam: isize) -> Result<()> {
+        let hwnd: HWND = self.handle.into();
+        if unsafe { PostMessageW(hwnd, msg, WPARAM(wparam), LPARAM(lparam)) }.as_bool() {
+            Ok(())
+        } else {
+            Err(Error::last_os_error())
+        }
+    }
+
+    fn post_key(&self, code: KeyCode, down: bool) -> Result<()> {
+        let (key, scan, extended) = match code {
+            KeyCode::Virtual(key) => {
+                let scan = unsafe { MapVirtualKeyW(key.0 as _, MAPVK_VK_TO_VSC) } as u16;
+                (key, scan, is_extended_key(key))
+            },
+            KeyCode::Scan(scan) => {
+                let extended = (scan >> 8) == 0xE0;
+                let code = if extended { 0xE000 | (scan & 0xFF) } else { scan };
+                let key = unsafe { MapVirtualKeyW(code as _, MAPVK_VSC_TO_VK_EX) } as u16;
+                (VIRTUAL_KEY(key), scan & 0xFF, extended)
+            }
+        };
+
+        {
+            let mut keys = self.keys.borrow_mut();
+            if down && !keys.contains(&key) {
+                keys.push(key);
+            } else if !down {
+                keys.retain(|k| *k != key);
+            }
+        }
+
+        let alt = self.is_alt_down() || (down && is_alt_key(key));
+        let msg = match (down, (alt && !self.is_ctrl_down()) || key == VK_F10) {
+            (true, false) => WM_KEYDOWN,
+            (true, true) => WM_SYSKEYDOWN,
+            (false, false) => WM_KEYUP,
+            (false, true) => WM_SYSKEYUP
+        };
+        self.post(msg, key.0 as usize, create_key_lparam(scan, extended, alt, !down))
+    }
+
+    fn post_char(&self, code: u16) -> Result<()> {
+        let alt = self.is_alt_down() && !self.is_ctrl_down();
+        let msg = if alt { WM_SYSCHAR } else { WM_CHAR };
+        self.post(msg, code as usize, create_key_lparam(0, false, alt, false))
+    }
+
+    fn post_button(&self, button: MouseButton, down: bool) -> Result<()> {
+        let flag = get_button_flag(button);
+        if down {
+            self.buttons.set(self.buttons.get() | flag);
+        } else {
+            self.buttons.set(self.buttons.get() & !flag);
+        }
+
+        let pos = self.cursor.get();
+        let double = down && self.is_double_click(button, pos);
+        let msg = get_button_message(button, down, double);
+        let wparam = match button {
+            MouseButton::X1 => (XBUTTON1.0 as usize) << 16,
+            MouseButton::X2 => (XBUTTON2.0 as usize) << 16,
+            _ => 0
+        } | self.get_mouse_flags() as usize;
+        self.post(msg, wparam, create_point_lparam(self.to_client(pos)?))
+    }
+
+    fn post_wheel(&self, msg: u32, delta: i32) -> Result<()> {
+        let wparam = ((delta as i16 as u16 as usize) << 16) | self.get_mouse_flags() as usize;
+        self.post(msg, wparam, create_point_lparam(self.cursor.get()))
+    }
+
+    /// Determines whether the button down makes a double click with the previous one, as the system does for real input.
+    fn is_double_click(&self, button: MouseButton, pos: Point) -> bool {
+        let now = Instant::now();
+        let time = Duration::from_millis(unsafe { GetDoubleClickTime() } as u64);
+        match self.last_click.get() {
+            Some((b, p, t)) if b == button && p == pos && now.duration_since(t) <= time => {
+                self.last_click.set(None);
+                true
+            },
+            _ => {
+                self.last_click.set(Some((button, pos, now)));
+                false
+            }
+        }
+    }
+
+    fn get_mouse_flags(&self) -> u32 {
+        let mut flags = self.buttons.get();
+        if self.is_ctrl_down() {
+            flags |= MK_CONTROL;
+        }
+        if self.keys.borrow().iter().any(|k| matches!(*k, VK_SHIFT | VK_LSHIFT | VK_RSHIFT)) {
+            flags |= MK_SHIFT;
+        }
+        flags
+    }
+
+    fn is_alt_down(&self) -> bool {
+        self.keys.borrow().iter().any(|k| is_alt_key(*k))
+    }
+
+    fn is_ctrl_down(&self) -> bool {
+        self.keys.borrow().iter().any(|k| matches!(*k, VK_CONTROL | VK_LCONTROL | VK_RCONTROL))
+    }
+
+    fn to_client(&self, pos: Point) -> Result<Point> {
+        let hwnd: HWND = self.handle.into();
+        let mut point: POINT = pos.into();
+        if unsafe { ScreenToClient(hwnd, &mut point) }.as_bool() {
+            Ok(point.into())
+        } else {
+            Err(Error::last_os_error())
+        }
+    }
+}
+
+impl InputSink for MessageSink {
+    fn send(&self, events: &[InputEvent]) -> Result<()> {
+        for event in events {
+            match event {
+                InputEvent::KeyDown(code) => self.post_key(*code, true)?,
+                InputEvent::KeyUp(code) => self.post_key(*code, false)?,
+                InputEvent::UnicodeDown(code) => self.post_char(*code)?,
+                InputEvent::UnicodeUp(_) => (),
+                InputEvent::Move(pos) | InputEvent::MoveAbsolute(pos) => {
+                    self.cursor.set(*pos);
+                    self.post(WM_MOUSEMOVE, self.get_mouse_flags() as usize, create_point_lparam(self.to_client(*pos)?))?;
+                },
+                InputEvent::ButtonDown(button) => self.post_button(*button, true)?,
+                InputEvent::ButtonUp(button) => self.post_button(*button, false)?,
+                InputEvent::Wheel(delta) => self.post_wheel(WM_MOUSEWHEEL, *delta)?,
+                InputEvent::HorizontalWheel(delta) => self.post_wheel(WM_MOUSEHWHEEL, *delta)?
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_cursor_pos(&self) -> Result<Point> {
+        Ok(self.cursor.get())
+    }
+
+    fn get_screen_size(&self) -> Result<(i32, i32)> {
+        get_screen_size()
+    }
+
+    fn get_monitors(&self) -> Result<Vec<Rect>> {
+        let monitors = Monitor::get_monitors()?;
+        Ok(monitors.iter().map(|m| m.get_bounds()).collect())
+    }
+
+    /// Characters are always posted as `WM_CHAR`, so the layout is empty.
+    fn get_keyboard_layout(&self) -> KeyboardLayout {
+        KeyboardLayout::new()
+    }
+
+    fn is_key_toggled(&self, _key: VIRTUAL_KEY) -> bool {
+        false
+    }
+
+    fn is_key_pressed(&self, key: VIRTUAL_KEY) -> bool {
+        self.keys.borrow().contains(&key)
+    }
+}
+
+fn is_alt_key(key: VIRTUAL_KEY) -> bool {
+    matches!(key, VK_MENU | VK_LMENU | VK_RMENU)
+}
+
+/// Determines whether the virtual key is on the extended part of the keyboard, such as the arrow keys and the right ctrl.
+fn is_extended_key(key: VIRTUAL_KEY) -> bool {
+    matches!(key, VK_RMENU | VK_RCONTROL | VK_INSERT | VK_DELETE | VK_HOME | VK_END | VK_PRIOR | VK_NEXT
+        | VK_LEFT | VK_UP | VK_RIGHT | VK_DOWN | VK_NUMLOCK | VK_DIVIDE | VK_SNAPSHOT | VK_LWIN | VK_RWIN | VK_APPS)
+}
+
+/// Creates the `lParam` of a keystroke message, which has the repeat count, the scan code, the extended key flag,
+/// the context code of alt, the previous key state and the transition state.
+fn create_key_lparam(scan: u16, extended: bool, alt: bool, up: bool) -> isize {
+    let mut lparam: u32 = 1 | ((scan as u32 & 0xFF) << 16);
+    if extended {
+        lparam |= 1 << 24;
+    }
+    if alt {
+        lparam |= 1 << 29;
+    }
+    if up {
+        lparam |= (1 << 30) | (1 << 31);
+    }
+    lparam as isize
+}
+
+/// Creates the `lParam` of a mouse message from the coordinates.
+fn create_point_lparam(pos: Point) -> isize {
+    let x = pos.get_x() as u16 as u32;
+    let y = pos.get_y() as u16 as u32;
+    ((y << 16) | x) as isize
+}
+
+fn get_button_flag(button: MouseButton) -> u32 {
+    match button {
+        MouseButton::Left => MK_LBUTTON,
+        MouseButton::Right => MK_RBUTTON,
+        MouseButton::Middle => MK_MBUTTON,
+        MouseButton::X1 => MK_XBUTTON1,
+        MouseButton::X2 => MK_XBUTTON2
+    }
+}
+
+fn get_button_message(button: MouseButton, down: bool, double: bool) -> u32 {
+    match (button, down, double) {
+        (MouseButton::Left, true, false) => WM_LBUTTONDOWN,
+        (MouseButton::Left, true, true) => WM_LBUTTONDBLCLK,
+        (MouseButton::Left, false, _) => WM_LBUTTONUP,
+        (MouseButton::Right, true, false) => WM_RBUTTONDOWN,
+        (MouseButton::Right, true, true) => WM_RBUTTONDBLCLK,
+        (MouseButton::Right, false, _) => WM_RBUTTONUP,
+        (MouseButton::Middle, true, false) => WM_MBUTTONDOWN,
+        (MouseButton::Middle, true, true) => WM_MBUTTONDBLCLK,
+        (MouseButton::Middle, false, _) => WM_MBUTTONUP,
+        (MouseButton::X1 | MouseButton::X2, true, false) => WM_XBUTTONDOWN,
+        (MouseButton::X1 | MouseButton::X2, true, true) => WM_XBUTTONDBLCLK,
+        (MouseButton::X1 | MouseButton::X2, false, _) => WM_XBUTTONUP
+    }
+}
+
+/// An `InputSink` which records the events instead of sending them to the system.
+/// 
+/// The cursor position follows the recorded `InputEvent::Move` events. Characters are typed as unicode unless a keyboard layout is set.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use uiautomation::inputs::InputEvent;
+/// use uiautomation::inputs::Keyboard;
+/// use uiautomation::inputs::RecordingSink;
+/// 
+/// let keyboard = Keyboard::with_sink(RecordingSink::new());
+/// keyboard.send_keys("{enter}").unwrap();
+/// assert_eq!(keyboard.get_sink().get_events().len(), 2);
+/// ```
+#[derive(Debug)]
+pub struct RecordingSink {
+    events: RefCell<Vec<InputEvent>>,
+    cursor: Cell<Point>,
+    screen_size: (i32, i32),
+    monitors: Vec<Rect>,
+    layout: KeyboardLayout,
+    toggled: RefCell<Vec<VIRTUAL_KEY>>
+}
+
+impl Default for RecordingSink {
+    fn default() -> Self {
+        Self {
+            events: RefCell::new(Vec::new()),
+            cursor: Cell::new(Point::default()),
+            screen_size: (1920, 1080),
+            monitors: Vec::new(),
+            layout: KeyboardLayout::new(),
+            toggled: RefCell::new(Vec::new())
+        }
+    }
+}
+
+impl RecordingSink {
+    /// Creates a `RecordingSink` with a `1920x1080` screen and the cursor at `(0, 0)`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `(width, height)` size of the recorded screen.
+    pub fn screen_size(mut self, width: i32, height: i32) -> Self {
+        self.screen_size = (width, height);
+        self
+    }
+
+    /// Sets the bounds of the recorded monitors. By default, there is only one monitor of the screen size.
+    pub fn monitors(mut self, monitors: &[Rect]) -> Self {
+        self.monitors = monitors.to_vec();
+        self
+    }
+
+    /// Sets the keyboard `layout` which is used to type characters.
+    pub fn layout(mut self, layout: KeyboardLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Sets the initial position of the recorded cursor.
+    pub fn cursor_pos(self, pos: Point) -> Self {
+        self.cursor.set(pos);
+        self
+    }
+
+    /// Turns the toggle `key` on, such as `VK_CAPITAL`. The recorded key down events switch the toggle keys.
+    pub fn toggled(self, key: VIRTUAL_KEY) -> Self {
+        self.toggled.borrow_mut().push(key);
+        self
+    }
+
+    /// Retrieves the recorded events.
+    pub fn get_events(&self) -> Vec<InputEvent> {
+        self.events.borrow().clone()
+    }
+
+    /// Clears the recorded events.
+    pub fn clear(&self) {
+        self.events.borrow_mut().clear();
+    }
+}
+
+impl InputSink for RecordingSink {
+    fn send(&self, events: &[InputEvent]) -> Result<()> {
+        for event in events {
+            match event {
+                InputEvent::Move(pos) | InputEvent::MoveAbsolute(pos) => self.cursor.set(*pos),
+                InputEvent::KeyDown(KeyCode::Virtual(key)) if LOCK_KEYS.contains(key) => {
+                    let mut toggled = self.toggled.borrow_mut();
+                    if toggled.contains(key) {
+                        toggled.retain(|k| k != key);
+                    } else {
+                        toggled.push(*key);
+                    }
+                },
+                _ => ()
+            }
+        }
+        self.events.borrow_mut().extend_from_slice(events);
+
+        Ok(())
+    }
+
+    fn get_cursor_pos(&self) -> Result<Point> {
+        Ok(self.cursor.get())
+    }
+
+    fn get_screen_size(&self) -> Result<(i32, i32)> {
+        Ok(self.screen_size)
+    }
+
+    fn get_monitors(&self) -> Result<Vec<Rect>> {
+        if self.monitors.is_empty() {
+            Ok(vec![Rect::new(0, 0, self.screen_size.0, self.screen_size.1)])
+        } else {
+            Ok(self.monitors.clone())
+        }
+    }
+
+    fn get_keyboard_layout(&self) -> KeyboardLayout {
+        self.layout.clone()
+    }
+
+    fn is_key_toggled(&self, key: VIRTUAL_KEY) -> bool {
+        self.toggled.borrow().contains(&key)
+    }
+
+    fn is_key_pressed(&self, key: VIRTUAL_KEY) -> bool {
+        let code = KeyCode::Virtual(key);
+        self.events.borrow().iter().rev().find_map(|event| match event {
+            InputEvent::KeyDown(k) if *k == code => Some(true),
+            InputEvent::KeyUp(k) if *k == code => Some(false),
+            _ => None
+        }).unwrap_or(false)
+    }
+}
+
+/// Simulate typing keys on keyboard.
+#[derive(Debug)]
+pub struct Keyboard<S: InputSink = SendInputSink> {
+    interval: u64,
+    profile: Option<TypingProfile>,
+    rng: Mutex<SmallRng>,
+    strategy: TypingStrategy,
+    layout: Option<KeyboardLayout>,
+    normalize_locks: bool,
+    warn_modifiers: bool,
+    pressed: Mutex<Vec<KeyCode>>,
+    sink: S
+}
+
+impl Default for Keyboard {
+    fn default() -> Self {
+        Self::with_sink(SendInputSink)
+    }
+}
+
+impl Keyboard {
+    /// Create a keyboard to simulate typing keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S: InputSink> Keyboard<S> {
+    /// Create a keyboard which sends the keys to the `sink`.
+    pub fn with_sink(sink: S) -> Self {
+        Self {
+            interval: 0,
+            profile: None,
+            rng: Mutex::new(create_rng(None)),
+            strategy: TypingStrategy::default(),
+            layout: None,
+            normalize_locks: false,
+            warn_modifiers: false,
+            pressed: Mutex::new(Vec::new()),
+            sink
+        }
+    }
+
+    /// Retrieves the sink which receives the keys.
+    pub fn get_sink(&self) -> &S {
+        &self.sink
+    }
+
+    /// Set the interval time between keys.
+    /// 
+    /// `interval` is the time number of milliseconds, `0` is default value.
+    pub fn interval(mut self, interval: u64) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the typing `profile`, which randomizes the delay between keys and the time to hold a key.
+    /// 
+    /// The profile replaces the fixed `interval`.
+    pub fn profile(mut self, profile: TypingProfile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// Sets the `seed` of the random typing profile, so the typing cadence is reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = Mutex::new(create_rng(Some(seed)));
+        self
+    }
+
+    /// Sets the strategy of typing characters. `TypingStrategy::Layout` is default value.
+    pub fn strategy(mut self, strategy: TypingStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Sets the keyboard `layout` which is used to type characters. 
+    /// 
+    /// By default, the layout of the foreground window is captured when typing.
+    pub fn layout(mut self, layout: KeyboardLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    /// Sets whether to turn off the caps lock, num lock and scroll lock before typing, and restore them after typing. Default is `false`.
+    /// 
+    /// The lock keys are restored even if the typing fails.
+    pub fn normalize_locks(mut self, normalize_locks: bool) -> Self {
+        self.normalize_locks = normalize_locks;
+        self
+    }
+
+    /// Sets whether to write a warning to the standard error when modifier keys are held down at the start of typing,
+    /// which usually means the user is pressing them and the typed keys will be changed. Default is `false`.
+    /// 
+    /// Use `get_held_modifiers()` to check the keys without writing to the standard error.
+    pub fn warn_modifiers(mut self, warn_modifiers: bool) -> Self {
+        self.warn_modifiers = warn_modifiers;
+        self
+    }
+
+    /// Simulates typing `keys` on keyboard.
+    /// 
+    /// `{}` is used for some special keys. For example: `{ctrl}{alt}{delete}`, `{shift}{home}`.
+    /// 
+    /// `()` is used for group keys. For example: `{ctrl}(AB)` types `Ctrl+A+B`.
+    /// 
+    /// `{` `}` `(` `)` can be quoted by `{}`. For example: `{{}Hi,{(}rust!{)}{}}` types `{Hi,(rust)}`.
+    /// 
+    /// Special keys and quoted characters can be repeated by a count. For example: `{TAB 5}`, `{DOWN*15}`, `{{ 3}`.
+    /// The count is at most `MAX_REPEAT_COUNT`.
+    /// The hold keys apply to all repetitions, so `{shift}{TAB 3}` types `Shift+Tab` three times.
+    /// 
+    /// Keys can also be sent by raw codes. For example: `{VK 0x5D}` sends a virtual key code and `{SC 0x1C}` sends a scan code.
+    /// All the supported key names can be retrieved by `get_key_names()`.
+    /// 
+    /// `{key down}` and `{key up}` press and release a key explicitly. For example: `{shift down}{down 3}{shift up}`.
+    /// The keys which are still down are released at the end of typing. A `{key up}` without a matching `{key down}` is a format error.
+    pub fn send_keys(&self, keys: &str) -> Result<()> {
+        let schedule = self.schedule_keys(keys)?;
+        self.send_and_release(&schedule)
+    }
+
+    /// Simulates typing the `text` literally, without parsing special keys and groups.
+    /// 
+    /// For example: `type_text("{enter}")` types the seven characters instead of the enter key.
+    pub fn type_text(&self, text: &str) -> Result<()> {
+        let expression = KeyExpression::from(vec![KeyItem::Text(String::from(text))]);
+        let schedule = self.schedule_inputs(&create_inputs(&expression));
+        self.send_and_release(&schedule)
+    }
+
+    /// Simulates pressing the `hotkey` in menu notation, such as `Ctrl+Shift+S`, `Alt+F4` or the sequence `Ctrl+K, Ctrl+C`.
+    /// 
+    /// See `HotkeySequence` for the notation.
+    pub fn press_hotkey(&self, hotkey: &str) -> Result<()> {
+        let sequence = HotkeySequence::parse(hotkey)?;
+        let schedule = self.schedule_inputs(&create_inputs(&sequence.to_expression()));
+        self.send_and_release(&schedule)
+    }
+
+    fn send_and_release(&self, schedule: &[ScheduledEvent]) -> Result<()> {
+        if self.warn_modifiers {
+            self.warn_held_modifiers();
+        }
+
+        let locks = if self.normalize_locks {
+            LockGuard::normalize(&self.sink)?
+        } else {
+            LockGuard::new(&self.sink)
+        };
+        let guard = PressedGuard::new(self);
+        let ret = self.send_schedule(schedule);
+
+        ret.and(guard.release()).and(locks.restore())
+    }
+
+    /// Retrieves the shift, ctrl, alt and windows keys which are held down but not pressed by this keyboard,
+    /// which usually means the user is pressing them and the typed keys will be changed.
+    pub fn get_held_modifiers(&self) -> Vec<VIRTUAL_KEY> {
+        let pressed = lock(&self.pressed);
+        KeyboardState::with_sink(&self.sink).get_pressed_modifiers().into_iter()
+            .filter(|key| !pressed.contains(&KeyCode::Virtual(*key)))
+            .collect()
+    }
+
+    fn warn_held_modifiers(&self) {
+        let held: Vec<String> = self.get_held_modifiers().into_iter()
+            .map(|key| Hotkey::new(0, key).to_string())
+            .collect();
+        if !held.is_empty() {
+            eprintln!("warning: the modifier keys {} are held down when typing starts", held.join(", "));
+        }
+    }
+
+    /// Releases the shift, ctrl, alt and windows keys which are pressed down, such as the keys left by an interrupted automation.
+    /// 
+    /// Returns the released keys.
+    pub fn release_all_modifiers(&self) -> Result<Vec<VIRTUAL_KEY>> {
+        let mut released: Vec<VIRTUAL_KEY> = Vec::new();
+        for key in MODIFIER_KEYS {
+            if self.sink.is_key_pressed(key) {
+                self.send_events(&[InputEvent::KeyUp(KeyCode::Virtual(key))])?;
+                released.push(key);
+            }
+        }
+
+        Ok(released)
+    }
+
+    /// Schedules the input events of typing `keys` without sending them.
+    /// 
+    /// Each event has a delay before it, which comes from the typing profile or the fixed interval.
+    pub fn schedule_keys(&self, keys: &str) -> Result<Vec<ScheduledEvent>> {
+        let inputs = parse_input(keys)?;
+        Ok(self.schedule_inputs(&inputs))
+    }
+
+    fn schedule_inputs(&self, inputs: &[Input]) -> Vec<ScheduledEvent> {
+        let has_chars = inputs.iter().any(|input| input.has_chars());
+        let layout = match self.layout {
+            Some(ref layout) => Cow::Borrowed(layout),
+            None if has_chars && self.strategy != TypingStrategy::Unicode => Cow::Owned(self.sink.get_keyboard_layout()),
+            None => Cow::Owned(KeyboardLayout::new())
+        };
+        let caps_lock = has_chars && self.strategy != TypingStrategy::Unicode && !self.normalize_locks && self.sink.is_key_toggled(VK_CAPITAL);
+        let typer = CharTyper::new(self.strategy, &layout, caps_lock);
+
+        let mut pressed = self.get_pressed_keys();
+        let strokes: Vec<Stroke> = inputs.iter().flat_map(|input| input.create_strokes(&typer, &mut pressed)).collect();
+
+        let schedule = if let Some(ref profile) = self.profile {
+            profile.schedule(&strokes, &mut *lock(&self.rng))
+        } else {
+            schedule_interval(&strokes, self.interval)
+        };
+        schedule
+    }
+
+    /// Retrieves the keys which are held down by the keyboard and not released yet.
+    pub fn get_pressed_keys(&self) -> Vec<KeyCode> {
+        lock(&self.pressed).clone()
+    }
+
+    fn release_pressed(&self) -> Result<()> {
+        let events: Vec<InputEvent> = lock(&self.pressed).iter().rev().map(|key| InputEvent::KeyUp(*key)).collect();
+        if events.is_empty() {
+            Ok(())
+        } else {
+            self.send_events(&events)
+        }
+    }
+
+    fn send_schedule(&self, schedule: &[ScheduledEvent]) -> Result<()> {
+        let mut events: Vec<InputEvent> = Vec::new();
+        for item in schedule {
+            if item.get_delay() > 0 {
+                if !events.is_empty() {
+                    self.send_events(&events)?;
+                    events.clear();
+                }
+                sleep(Duration::from_millis(item.get_delay()));
+            }
+            events.push(item.get_event());
+        }
+
+        if events.is_empty() {
+            Ok(())
+        } else {
+            self.send_events(&events)
+        }
+    }
+
+    fn send_events(&self, events: &[InputEvent]) -> Result<()> {
+        let ret = self.sink.send(events);
+
+        let mut pressed = lock(&self.pressed);
+        for event in events {
+            match event {
+                InputEvent::KeyDown(key) if !pressed.contains(key) => pressed.push(*key),
+                // the batch may fail midway after some keys are pressed, so they are all released again.
+                InputEvent::KeyUp(key) if ret.is_ok() => pressed.retain(|k| k != key),
+                _ => ()
+            }
+        }
+
+        ret
+    }
+}
+
+/// Locks the `mutex`, which is still usable after a panic of another thread holding it.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Switches the toggled lock keys off, and switches them back on when restored or dropped.
+struct LockGuard<'a, S: InputSink> {
+    sink: &'a S,
+    keys: Vec<VIRTUAL_KEY>
+}
+
+impl<'a, S: InputSink> LockGuard<'a, S> {
+    /// Creates a guard which restores nothing.
+    fn new(sink: &'a S) -> Self {
+        Self {
+            sink,
+            keys: Vec::new()
+        }
+    }
+
+    /// Switches off the lock keys which are on.
+    fn normalize(sink: &'a S) -> Result<Self> {
+        let keys: Vec<VIRTUAL_KEY> = LOCK_KEYS.iter().copied().filter(|key| sink.is_key_toggled(*key)).collect();
+        toggle_keys(sink, &keys)?;
+
+        Ok(Self {
+            sink,
+            keys
+        })
+    }
+
+    fn restore(mut self) -> Result<()> {
+        let keys = mem::take(&mut self.keys);
+        toggle_keys(self.sink, &keys)
+    }
+}
+
+impl<'a, S: InputSink> Drop for LockGuard<'a, S> {
+    fn drop(&mut self) {
+        let keys = mem::take(&mut self.keys);
+        let _ = toggle_keys(self.sink, &keys);
+    }
+}
+
+fn toggle_keys<S: InputSink>(sink: &S, keys: &[VIRTUAL_KEY]) -> Result<()> {
+    let events: Vec<InputEvent> = keys.iter()
+        .flat_map(|key| [InputEvent::KeyDown(KeyCode::Virtual(*key)), InputEvent::KeyUp(KeyCode::Virtual(*key))])
+        .collect();
+    if events.is_empty() {
+        Ok(())
+    } else {
+        sink.send(&events)
+    }
+}
+
+/// Reads whether the keys are pressed or toggled.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use uiautomation::inputs::InputEvent;
+/// use uiautomation::inputs::InputSink;
+/// use uiautomation::inputs::KeyCode;
+/// use uiautomation::inputs::KeyboardState;
+/// use uiautomation::inputs::RecordingSink;
+/// use windows::Win32::UI::Input::KeyboardAndMouse::*;
+/// 
+/// let sink = RecordingSink::new().toggled(VK_NUMLOCK);
+/// sink.send(&[InputEvent::KeyDown(KeyCode::Virtual(VK_SHIFT))]).unwrap();
+/// 
+/// let state = KeyboardState::with_sink(&sink);
+/// assert!(state.is_pressed(VK_SHIFT));
+/// assert!(state.is_toggled(VK_NUMLOCK));
+/// assert_eq!(state.get_pressed_modifiers(), vec![VK_SHIFT]);
+/// assert_eq!(state.get_toggled_locks(), vec![VK_NUMLOCK]);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyboardState<S: InputSink = SendInputSink> {
+    sink: S
+}
+
+impl KeyboardState {
+    /// Creates a keyboard state which reads the system keyboard.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S: InputSink> KeyboardState<S> {
+    /// Creates a keyboard state which reads the keys from the `sink`.
+    pub fn with_sink(sink: S) -> Self {
+        Self {
+            sink
+        }
+    }
+
+    /// Determines whether the `key` is pressed down.
+    pub fn is_pressed(&self, key: VIRTUAL_KEY) -> bool {
+        self.sink.is_key_pressed(key)
+    }
+
+    /// Determines whether the toggle `key`, such as `VK_CAPITAL`, `VK_NUMLOCK` or `VK_SCROLL`, is on.
+    pub fn is_toggled(&self, key: VIRTUAL_KEY) -> bool {
+        self.sink.is_key_toggled(key)
+    }
+
+    /// Retrieves the shift, ctrl, alt and windows keys which are pressed down.
+    pub fn get_pressed_modifiers(&self) -> Vec<VIRTUAL_KEY> {
+        MODIFIER_KEYS.iter().copied().filter(|key| self.is_pressed(*key)).collect()
+    }
+
+    /// Retrieves the caps lock, num lock and scroll lock keys which are on.
+    pub fn get_toggled_locks(&self) -> Vec<VIRTUAL_KEY> {
+        LOCK_KEYS.iter().copied().filter(|key| self.is_toggled(*key)).collect()
+    }
+}
+
+/// Releases the keys pressed by a keyboard when dropped, including on error, early return and unwind.
+struct PressedGuard<'a, S: InputSink> {
+    keyboard: Option<&'a Keyboard<S>>
+}
+
+impl<'a, S: InputSink> PressedGuard<'a, S> {
+    fn new(keyboard: &'a Keyboard<S>) -> Self {
+        Self {
+            keyboard: Some(keyboard)
+        }
+    }
+
+    fn release(mut self) -> Result<()> {
+        match self.keyboard.take() {
+            Some(keyboard) => keyboard.release_pressed(),
+            None => Ok(())
+        }
+    }
+}
+
+impl<'a, S: InputSink> Drop for PressedGuard<'a, S> {
+    fn drop(&mut self) {
+        if let Some(keyboard) = self.keyboard.take() {
+            let _ = keyboard.release_pressed();
+        }
+    }
+}
+
+/// Holds keys and mouse buttons down, and releases them in reverse order when dropped, including on error, early return and unwind.
+struct HoldGuard<'a, S: InputSink> {
+    sink: &'a S,
+    interval: u64,
+    held: Vec<InputEvent>
+}
+
+impl<'a, S: InputSink> HoldGuard<'a, S> {
+    /// Creates a guard which waits for `interval` milliseconds after each event.
+    fn new(sink: &'a S, interval: u64) -> Self {
+        Self {
+            sink,
+            interval,
+            held: Vec::new()
+        }
+    }
+
+    fn key_down(&mut self, key: KeyCode) -> Result<()> {
+        self.press(InputEvent::KeyDown(key), InputEvent::KeyUp(key))
+    }
+
+    fn button_down(&mut self, button: MouseButton) -> Result<()> {
+        self.press(InputEvent::ButtonDown(button), InputEvent::ButtonUp(button))
+    }
+
+    fn button_up(&mut self, button: MouseButton) -> Result<()> {
+        let event = InputEvent::ButtonUp(button);
+        self.send(event)?;
+        // keeps the button held if the release fails, so it is released again when dropped.
+        self.held.retain(|e| *e != event);
+
+        Ok(())
+    }
+
+    /// Releases all the held keys and buttons.
+    fn release(mut self) -> Result<()> {
+        self.release_held()
+    }
+
+    fn press(&mut self, down: InputEvent, up: InputEvent) -> Result<()> {
+        self.sink.send(&[down])?;
+        self.held.push(up);
+        self.wait();
+
+        Ok(())
+    }
+
+    fn release_held(&mut self) -> Result<()> {
+        let mut ret = Ok(());
+        while let Some(event) = self.held.pop() {
+            ret = ret.and(self.send(event));
+        }
+        ret
+    }
+
+    fn send(&self, event: InputEvent) -> Result<()> {
+        self.sink.send(&[event])?;
+        self.wait();
+
+        Ok(())
+    }
+
+    fn wait(&self) {
+        if self.interval > 0 {
+            sleep(Duration::from_millis(self.interval));
+        }
+    }
+}
+
+impl<'a, S: InputSink> Drop for HoldGuard<'a, S> {
+    fn drop(&mut self) {
+        let _ = self.release_held();
+    }
+}
+
+/// Simulate mouse event.
+#[derive(Debug)]
+pub struct Mouse<S: InputSink = SendInputSink> {
+    interval: u64,
+    move_time: u64,
+    auto_move: bool,
+    absolute: bool,
+    hold_time: u64,
+    drop_time: u64,
+    trajectory: Option<Trajectory>,
+    rng: Mutex<SmallRng>,
+    holdkeys: Vec<VIRTUAL_KEY>,
+    sink: S
+}
+
+impl Default for Mouse {
+    fn default() -> Self {
+        Self::with_sink(SendInputSink)
+    }
+}
+
+impl Mouse {
+    /// Creates a `Mouse` to simulate mouse event.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Retrieves the position of the mouse cursor, in screen coordinates.
+    pub fn get_cursor_pos() -> Result<Point> {
+        SendInputSink.get_cursor_pos()
+    }
+
+    /// Moves the cursor to the specified screen coordinates. 
+    pub fn set_cursor_pos(pos: Point) -> Result<()> {
+        SendInputSink.send(&[InputEvent::Move(pos)])
+    }
+}
+
+impl<S: InputSink> Mouse<S> {
+    /// Creates a `Mouse` which sends the mouse events to the `sink`.
+    pub fn with_sink(sink: S) -> Self {
+        Self { 
+            interval: 100, 
+            move_time: 500,
+            auto_move: true,
+            absolute: false,
+            hold_time: 200,
+            drop_time: 200,
+            trajectory: None,
+            rng: Mutex::new(create_rng(None)),
+            holdkeys: Vec::new(),
+            sink
+        }
+    }
+
+    /// Retrieves the sink which receives the mouse events.
+    pub fn get_sink(&self) -> &S {
+        &self.sink
+    }
+
+    /// Sets the interval time between events.
+    /// 
+    /// `interval` is the time number of milliseconds, `100` is default value.
+    pub fn interval(mut self, interval: u64) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the mouse move time in millionseconds. `500` is default value.
+    pub fn move_time(mut self, move_time: u64) -> Self {
+        self.move_time = move_time;
+        self
+    }
+
+    /// Sets whether move the cursor to the click point automatically. Default is `true`.
+    pub fn auto_move(mut self, auto_move: bool) -> Self {
+        self.auto_move = auto_move;
+        self
+    }
+
+    /// Sets whether move the cursor by absolute virtual-desktop mouse events instead of setting the cursor position. Default is `false`.
+    pub fn absolute(mut self, absolute: bool) -> Self {
+        self.absolute = absolute;
+        self
+    }
+
+    /// Sets the `trajectory` of the cursor movements. 
+    /// 
+    /// By default, the cursor moves in uniform linear steps of 20 pixels.
+    pub fn trajectory(mut self, trajectory: Trajectory) -> Self {
+        self.trajectory = Some(trajectory);
+        self
+    }
+
+    /// Sets the `seed` of the random trajectories, so the movements are reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = Mutex::new(create_rng(Some(seed)));
+        self
+    }
+
+    /// Sets the time in millionseconds to hold the button before moving when dragging. `200` is default value.
+    pub fn hold_time(mut self, hold_time: u64) -> Self {
+        self.hold_time = hold_time;
+        self
+    }
+
+    /// Sets the time in millionseconds to wait at the target before releasing the button when dragging. `200` is default value.
+    pub fn drop_time(mut self, drop_time: u64) -> Self {
+        self.drop_time = drop_time;
+        self
+    }
+
+    /// Sets the holdkeys when mouse clicks.
+    /// 
+    /// The holdkeys is quoted by `{}`. For example: `{Shift}`, `{Ctrl}{Alt}`.
+    pub fn holdkeys(mut self, holdkeys: &str) -> Self {
+        self.holdkeys.clear();
+
+        let expr = KeyExpression::parse(holdkeys).unwrap();
+        for item in expr.get_items() {
+            if let KeyItem::HoldKey(key) = item {
+                self.holdkeys.push(key.get_key());
+            }
+        }
+            
+        self
+    }
+
+    /// Moves the cursor from current position to the `target` position.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use uiautomation::inputs::Mouse;
+    /// use uiautomation::types::Point;
+    /// 
+    /// let mouse = Mouse::new().move_time(800);
+    /// mouse.move_to(Point::new(10, 20)).unwrap();
+    /// mouse.move_to(Point::new(1000,800)).unwrap();
+    /// ```
+    pub fn move_to(&self, target: Point) -> Result<()> {
+        let monitors = self.sink.get_monitors()?;
+        let target = clamp_to_monitors(target, &monitors);
+
+        if let Some(ref trajectory) = self.trajectory {
+            let source = self.sink.get_cursor_pos()?;
+            let points = trajectory.generate(source, target, self.move_time, &mut *lock(&self.rng));
+            let mut time = 0;
+            for point in points {
+                if point.get_time() > time {
+                    sleep(Duration::from_millis(point.get_time() - time));
+                    time = point.get_time();
+                }
+                self.move_event(point.get_point())?;
+            }
+
+            return Ok(());
+        }
+        
+        if self.move_time > 0 {
+            let source = self.sink.get_cursor_pos()?;
+            let delta_x = target.get_x() - source.get_x();
+            let delta_y = target.get_y() - source.get_y();
+
+            let delta = max(delta_x.abs(), delta_y.abs());
+            let steps = delta / 20;
+            if steps > 1 {
+                let step_x = delta_x / steps;
+                let step_y = delta_y / steps;
+                let interval = Duration::from_millis(self.move_time / steps as u64);
+                for i in 1..steps {
+                    let pos = Point::new(
+                        source.get_x() + step_x * i, 
+                        source.get_y() + step_y * i
+                    );
+                    self.move_event(pos)?;
+                    sleep(interval);
+                }
+            }
+        }
+
+        self.move_event(target)
+    }
+
+    /// Simulates a mouse click event.
+    /// 
+    /// # Examples
+    /// ```
+    /// use uiautomation::inputs::Mouse;
+    /// 
+    /// let mouse = Mouse::new();
+    /// let pos = Mouse::get_cursor_pos().unwrap();
+    /// mouse.click(pos).unwrap();
+    /// ```
+    pub fn click(&self, pos: Point) -> Result<()> {
+        self.button_click(pos, MouseButton::Left)
     }
 
     /// Simulates a mouse double click event.
@@ -612,22 +2262,83 @@ impl Mouse {
             self.move_to(pos)?;
         }
 
-        self.before_click()?;
+        let mut guard = self.before_click()?;
 
-        self.mouse_event(pos.get_x(), pos.get_y(), MOUSEEVENTF_LEFTDOWN)?;
-        self.mouse_event(pos.get_x(), pos.get_y(), MOUSEEVENTF_LEFTUP)?;
+        guard.button_down(MouseButton::Left)?;
+        guard.button_up(MouseButton::Left)?;
 
         sleep(Duration::from_millis(max(200, self.interval)));
 
-        self.mouse_event(pos.get_x(), pos.get_y(), MOUSEEVENTF_LEFTDOWN)?;
-        self.mouse_event(pos.get_x(), pos.get_y(), MOUSEEVENTF_LEFTUP)?;
+        guard.button_down(MouseButton::Left)?;
+        guard.button_up(MouseButton::Left)?;
 
-        self.after_click()?;
+        guard.release()
+    }
 
-        Ok(())
+    /// Simulates a right mouse click event.
+    /// 
+    /// # Examples
+    /// ```
+    /// use uiautomation::inputs::Mouse;
+    /// 
+    /// let mouse = Mouse::new();
+    /// let pos = Mouse::get_cursor_pos().unwrap();
+    /// mouse.right_click(pos).unwrap();
+    /// ```
+    pub fn right_click(&self, pos: Point) -> Result<()> {
+        self.button_click(pos, MouseButton::Right)
+    }
+
+    /// Simulates a middle mouse click event.
+    /// 
+    /// # Examples
+    /// ```
+    /// use uiautomation::inputs::Mouse;
+    /// 
+    /// let mouse = Mouse::new();
+    /// let pos = Mouse::get_cursor_pos().unwrap();
+    /// mouse.middle_click(pos).unwrap();
+    /// ```
+    pub fn middle_click(&self, pos: Point) -> Result<()> {
+        self.button_click(pos, MouseButton::Middle)
+    }
+
+    /// Presses the mouse `button` at the current position, including the `X1` and `X2` buttons.
+    pub fn press(&self, button: MouseButton) -> Result<()> {
+        self.mouse_event(button, true)
+    }
+
+    /// Releases the mouse `button` at the current position.
+    pub fn release(&self, button: MouseButton) -> Result<()> {
+        self.mouse_event(button, false)
+    }
+
+    /// Drags with the left button from the `from` position and drops at the `to` position.
+    /// 
+    /// The button is held for `hold_time` before moving, and the cursor waits for `drop_time` at the target before releasing.
+    /// 
+    /// # Examples
+    /// ```
+    /// use uiautomation::inputs::Mouse;
+    /// use uiautomation::types::Point;
+    /// 
+    /// let mouse = Mouse::new().hold_time(300);
+    /// mouse.drag(Point::new(100, 100), Point::new(300, 200)).unwrap();
+    /// ```
+    pub fn drag(&self, from: Point, to: Point) -> Result<()> {
+        self.move_to(from)?;
+
+        let mut guard = self.before_click()?;
+        guard.button_down(MouseButton::Left)?;
+        let ret = self.drag_move(to);
+        let released = guard.button_up(MouseButton::Left);
+
+        ret.and(released).and(guard.release())
     }
 
-    /// Simulates a right mouse click event.
+    /// Rotates the vertical wheel by `ticks` notches at the `pos` position.
+    /// 
+    /// A positive value rotates forward, away from the user, which usually scrolls up.
     /// 
     /// # Examples
     /// ```
@@ -635,56 +2346,77 @@ impl Mouse {
     /// 
     /// let mouse = Mouse::new();
     /// let pos = Mouse::get_cursor_pos().unwrap();
-    /// mouse.right_click(pos).unwrap();
+    /// mouse.scroll_wheel(pos, -3).unwrap();
     /// ```
-    pub fn right_click(&self, pos: Point) -> Result<()> {
+    pub fn scroll_wheel(&self, pos: Point, ticks: i32) -> Result<()> {
+        self.wheel_event(pos, InputEvent::Wheel(ticks * WHEEL_DELTA))
+    }
+
+    /// Rotates the horizontal wheel by `ticks` notches at the `pos` position.
+    /// 
+    /// A positive value rotates to the right.
+    pub fn scroll_hwheel(&self, pos: Point, ticks: i32) -> Result<()> {
+        self.wheel_event(pos, InputEvent::HorizontalWheel(ticks * WHEEL_DELTA))
+    }
+
+    fn button_click(&self, pos: Point, button: MouseButton) -> Result<()> {
         if self.auto_move {
             self.move_to(pos)?;
         }
 
-        self.before_click()?;
-        self.mouse_event(pos.get_x(), pos.get_y(), MOUSEEVENTF_RIGHTDOWN)?;
-        self.mouse_event(pos.get_x(), pos.get_y(), MOUSEEVENTF_RIGHTUP)?;
-        self.after_click()?;
+        let mut guard = self.before_click()?;
+        guard.button_down(button)?;
+        guard.button_up(button)?;
+
+        guard.release()
+    }
+
+    fn drag_move(&self, to: Point) -> Result<()> {
+        sleep(Duration::from_millis(self.hold_time));
+        self.move_to(to)?;
+        sleep(Duration::from_millis(self.drop_time));
 
         Ok(())
     }
 
-    fn before_click(&self) -> Result<()> {
-        for holdkey in &self.holdkeys {
-            let key_input = [Input::create_virtual_key(*holdkey, KEYEVENTF_KEYDOWN)];
-            send_input(&key_input)?;
-            self.wait();
+    fn wheel_event(&self, pos: Point, event: InputEvent) -> Result<()> {
+        if self.auto_move {
+            self.move_to(pos)?;
         }
 
-        Ok(())
+        let guard = self.before_click()?;
+        self.sink.send(&[event])?;
+        self.wait();
+
+        guard.release()
     }
 
-    fn after_click(&self) -> Result<()> {
+    /// Presses the holdkeys down, which are released by the returned guard.
+    fn before_click(&self) -> Result<HoldGuard<'_, S>> {
+        let mut guard = HoldGuard::new(&self.sink, self.interval);
         for holdkey in &self.holdkeys {
-            let key_input = [Input::create_virtual_key(*holdkey, KEYEVENTF_KEYUP)];
-            send_input(&key_input)?;
-            self.wait();
+            guard.key_down(KeyCode::Virtual(*holdkey))?;
         }
 
-        Ok(())
+        Ok(guard)
     }
 
-    fn mouse_event(&self, x: i32, y: i32, flags: MOUSE_EVENT_FLAGS) -> Result<()> {
-        let input = [INPUT {
-            r#type: INPUT_MOUSE,
-            Anonymous: INPUT_0 { 
-                mi: MOUSEINPUT { 
-                    dx: x, 
-                    dy: y, 
-                    mouseData: 0, 
-                    dwFlags: flags, 
-                    time: 0, 
-                    dwExtraInfo: 0 
-                }
-            }
-        }];
-        send_input(&input)?;
+    fn move_event(&self, pos: Point) -> Result<()> {
+        let event = if self.absolute {
+            InputEvent::MoveAbsolute(pos)
+        } else {
+            InputEvent::Move(pos)
+        };
+        self.sink.send(&[event])
+    }
+
+    fn mouse_event(&self, button: MouseButton, down: bool) -> Result<()> {
+        let event = if down {
+            InputEvent::ButtonDown(button)
+        } else {
+            InputEvent::ButtonUp(button)
+        };
+        self.sink.send(&[event])?;
         self.wait();
 
         Ok(())
@@ -697,6 +2429,13 @@ impl Mouse {
     }
 }
 
+/// Releases the shift, ctrl, alt and windows keys which are pressed down in the system.
+/// 
+/// It recovers the desktop session after an automation is interrupted with modifiers held, and returns the released keys.
+pub fn release_all_modifiers() -> Result<Vec<VIRTUAL_KEY>> {
+    Keyboard::new().release_all_modifiers()
+}
+
 /// Retrieves the `(width, height)` size of the primary screen.
 pub fn get_screen_size() -> Result<(i32, i32)> {
     let width = unsafe { GetSystemMetrics(SM_CXSCREEN) };
@@ -726,13 +2465,51 @@ fn send_input(inputs: &[INPUT]) -> Result<()> {
 
 #[cfg(test)]
 mod tests {
-    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+    use std::cell::Cell;
 
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+    use windows::Win32::UI::WindowsAndMessaging::WM_LBUTTONDBLCLK;
+    use windows::Win32::UI::WindowsAndMessaging::WM_LBUTTONDOWN;
+    use windows::Win32::UI::WindowsAndMessaging::WM_XBUTTONUP;
+
+    use crate::Error;
+    use crate::Result;
+    use crate::inputs::KeyCode;
+    use crate::inputs::KeyExpression;
+    use crate::inputs::KeyItem;
     use crate::inputs::Keyboard;
+    use crate::inputs::KeyboardState;
     use crate::inputs::parse_input;
     use crate::inputs::Input;
+    use crate::inputs::InputEvent;
     use crate::inputs::InputItem;
+    use crate::inputs::InputSink;
+    use crate::inputs::Mouse;
+    use crate::inputs::MouseButton;
+    use crate::inputs::RecordingSink;
+    use crate::inputs::CharTyper;
+    use crate::inputs::HoldGuard;
+    use crate::inputs::create_key_lparam;
+    use crate::inputs::escape_keys;
+    use crate::inputs::create_point_lparam;
+    use crate::inputs::get_button_message;
+    use crate::inputs::is_extended_key;
+    use crate::inputs::TypingStrategy;
+    use crate::layouts::KeyStroke;
+    use crate::layouts::KeyboardLayout;
+    use crate::layouts::MODIFIER_ALT;
+    use crate::layouts::MODIFIER_CTRL;
+    use crate::layouts::MODIFIER_SHIFT;
     use crate::inputs::VIRTUAL_KEYS;
+    use crate::inputs::get_key_names;
+    use crate::inputs::get_virtual_key;
+    use crate::inputs::MAX_REPEAT_COUNT;
+    use crate::trajectory::Trajectory;
+    use crate::typing::TypingProfile;
+    use crate::trajectory::TrajectoryProfile;
+    use crate::trajectory::create_rng;
+    use crate::types::Point;
+    use crate::types::Rect;
 
     #[test]
     fn test_virtual_keys() {
@@ -740,6 +2517,20 @@ mod tests {
         assert_eq!(key, Some(&VK_LBUTTON));
     }
 
+    #[test]
+    fn test_key_lookup() {
+        assert_eq!(get_virtual_key("numpad7"), Some(VK_NUMPAD7));
+        assert_eq!(get_virtual_key("MEDIA_PLAY_PAUSE"), Some(VK_MEDIA_PLAY_PAUSE));
+        assert_eq!(get_virtual_key("vk 0x5d"), Some(VK_APPS));
+        assert_eq!(get_virtual_key("VK 93"), Some(VK_APPS));
+        assert_eq!(get_virtual_key("VK 0x1FF"), None);
+        assert_eq!(get_virtual_key("SC 0x1C"), None);
+
+        let names = get_key_names();
+        assert!(names.contains(&"OEM_PLUS"));
+        assert!(names.windows(2).all(|w| w[0] < w[1]));
+    }
+
     #[test]
     fn show_desktop() {
         let kb = Keyboard::default().interval(50);
@@ -810,12 +2601,592 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_parse_input_7() {
+        assert_eq!(
+            parse_input("{TAB 3}x{down*2}").unwrap(),
+            vec![
+                Input {
+                    holdkeys: Vec::new(),
+                    items: vec![
+                        InputItem::VirtualKey(VK_TAB), InputItem::VirtualKey(VK_TAB), InputItem::VirtualKey(VK_TAB),
+                        InputItem::Character('x'),
+                        InputItem::VirtualKey(VK_DOWN), InputItem::VirtualKey(VK_DOWN)
+                    ]
+                }
+            ]
+        )
+    }
+
+    #[test]
+    fn test_parse_input_8() {
+        assert_eq!(
+            parse_input("{shift}{TAB 2}{tab}").unwrap(),
+            vec![
+                Input {
+                    holdkeys: vec![VK_SHIFT],
+                    items: vec![InputItem::VirtualKey(VK_TAB), InputItem::VirtualKey(VK_TAB)]
+                },
+                Input {
+                    holdkeys: Vec::new(),
+                    items: vec![InputItem::VirtualKey(VK_TAB)]
+                }
+            ]
+        )
+    }
+
+    #[test]
+    fn test_parse_input_9() {
+        assert_eq!(
+            parse_input("{( 2}{} 3}").unwrap(),
+            vec![
+                Input {
+                    holdkeys: Vec::new(),
+                    items: vec![
+                        InputItem::Character('('), InputItem::Character('('),
+                        InputItem::Character('}'), InputItem::Character('}'), InputItem::Character('}')
+                    ]
+                }
+            ]
+        )
+    }
+
+    #[test]
+    fn test_parse_input_10() {
+        assert_eq!(
+            parse_input("{ctrl}{shift down}ab{shift up}c").unwrap(),
+            vec![
+                Input {
+                    holdkeys: vec![VK_CONTROL],
+                    items: Vec::new()
+                },
+                Input {
+                    holdkeys: Vec::new(),
+                    items: vec![
+                        InputItem::KeyDown(KeyCode::Virtual(VK_SHIFT)), InputItem::Character('a'), InputItem::Character('b'),
+                        InputItem::KeyUp(KeyCode::Virtual(VK_SHIFT)), InputItem::Character('c')
+                    ]
+                }
+            ]
+        )
+    }
+
+    #[test]
+    fn test_parse_input_11() {
+        assert_eq!(
+            parse_input("{VK 0x5D}{sc 0x1C 2}{numpad1}{SC 28 down}").unwrap(),
+            vec![
+                Input {
+                    holdkeys: Vec::new(),
+                    items: vec![
+                        InputItem::VirtualKey(VK_APPS), InputItem::ScanCode(0x1C), InputItem::ScanCode(0x1C),
+                        InputItem::VirtualKey(VK_NUMPAD1), InputItem::KeyDown(KeyCode::Scan(0x1C))
+                    ]
+                }
+            ]
+        );
+
+        let err = KeyExpression::parse("{VK 0xZZ}").unwrap_err();
+        assert_eq!(err.get_token(), "{VK 0xZZ}");
+
+        assert!(KeyExpression::parse("{SC 0xE01C}").is_ok());
+        assert!(KeyExpression::parse("{SC 0x1FF}").is_err());
+        assert!(KeyExpression::parse("{SC 0xE11D}").is_err());
+        assert!(KeyExpression::parse("{SC 0xE000}").is_err());
+    }
+
+    #[test]
+    fn test_parse_input_12() {
+        let inputs = parse_input("{TAB 1000}").unwrap();
+        assert_eq!(inputs[0].items.len(), MAX_REPEAT_COUNT);
+
+        let err = KeyExpression::parse("ab{TAB 4000000000}").unwrap_err();
+        assert_eq!(err.get_offset(), 2);
+        assert_eq!(err.get_token(), "{TAB 4000000000}");
+        assert!(err.to_string().contains("Repeat count too large"));
+        assert!(KeyExpression::parse("{{ 1001}").is_err());
+    }
+
+    #[test]
+    fn test_parse_key_state_error() {
+        let err = KeyExpression::parse("a{shift up}").unwrap_err();
+        assert_eq!(err.get_offset(), 1);
+        assert_eq!(err.get_token(), "{shift up}");
+
+        let err = KeyExpression::parse("{ctrl down}(a{control down})").unwrap_err();
+        assert_eq!(err.get_offset(), 13);
+        assert_eq!(err.get_token(), "{control down}");
+
+        assert!(KeyExpression::parse("{shift down}{shift up}{shift up}").is_err());
+        assert!(KeyExpression::parse("{shift down}{lshift down}a").is_ok());
+        assert!(KeyExpression::parse("{DOWN down}{PAGE_UP}{down up}").is_ok());
+    }
+
+    #[test]
+    fn test_parse_repeat_error() {
+        let err = KeyExpression::parse("ab{TAB 0}").unwrap_err();
+        assert_eq!(err.get_offset(), 2);
+        assert_eq!(err.get_token(), "{TAB 0}");
+
+        assert!(KeyExpression::parse("{ctrl 2}").is_err());
+        assert!(KeyExpression::parse("{TAB 99999999999999999999999}").is_err());
+        assert!(KeyExpression::parse("{TAB x}").is_err());
+    }
+
     #[test]
     fn test_zh_input() {
         let inputs = parse_input("你好").unwrap();
         for input in &inputs {
-            let keys = input.create_inputs();
-            assert!(keys.is_ok());
+            let strokes = input.create_strokes(&CharTyper::new(TypingStrategy::Layout, &KeyboardLayout::new(), false), &mut Vec::new());
+            assert_eq!(strokes.iter().map(|s| s.events.len()).sum::<usize>(), 4);
+        }
+    }
+
+    #[test]
+    fn test_record_keys() {
+        let keyboard = Keyboard::with_sink(RecordingSink::new());
+        keyboard.send_keys("{ctrl}a{shift down}{TAB 2}").unwrap();
+        assert_eq!(
+            keyboard.get_sink().get_events(),
+            vec![
+                InputEvent::KeyDown(KeyCode::Virtual(VK_CONTROL)),
+                InputEvent::UnicodeDown('a' as u16),
+                InputEvent::UnicodeUp('a' as u16),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_CONTROL)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_SHIFT)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_TAB)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_TAB)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_TAB)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_TAB)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_SHIFT))
+            ]
+        );
+        assert!(keyboard.get_pressed_keys().is_empty());
+    }
+
+    fn german_layout() -> KeyboardLayout {
+        KeyboardLayout::new()
+            .key('e', KeyStroke::new(VK_E, 0x12, 0))
+            .key('E', KeyStroke::new(VK_E, 0x12, MODIFIER_SHIFT))
+            .key('@', KeyStroke::new(VK_Q, 0x10, MODIFIER_CTRL | MODIFIER_ALT))
+            .key('{', KeyStroke::new(VK_7, 0x08, MODIFIER_CTRL | MODIFIER_ALT))
+            .dead_key('é', KeyStroke::new(VK_OEM_6, 0x0D, 0), KeyStroke::new(VK_E, 0x12, 0))
+    }
+
+    #[test]
+    fn test_layout_typing() {
+        let keyboard = Keyboard::with_sink(RecordingSink::new().layout(german_layout()));
+        keyboard.send_keys("@é€").unwrap();
+        assert_eq!(
+            keyboard.get_sink().get_events(),
+            vec![
+                InputEvent::KeyDown(KeyCode::Virtual(VK_CONTROL)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_MENU)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_Q)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_Q)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_MENU)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_CONTROL)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_OEM_6)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_OEM_6)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_E)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_E)),
+                InputEvent::UnicodeDown('€' as u16),
+                InputEvent::UnicodeUp('€' as u16)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_code_typing() {
+        let keyboard = Keyboard::with_sink(RecordingSink::new()).strategy(TypingStrategy::ScanCode).layout(german_layout());
+        keyboard.send_keys("E{{}").unwrap();
+        assert_eq!(
+            keyboard.get_sink().get_events(),
+            vec![
+                InputEvent::KeyDown(KeyCode::Scan(0x2A)),
+                InputEvent::KeyDown(KeyCode::Scan(0x12)),
+                InputEvent::KeyUp(KeyCode::Scan(0x12)),
+                InputEvent::KeyUp(KeyCode::Scan(0x2A)),
+                InputEvent::KeyDown(KeyCode::Scan(0x1D)),
+                InputEvent::KeyDown(KeyCode::Scan(0x38)),
+                InputEvent::KeyDown(KeyCode::Scan(0x08)),
+                InputEvent::KeyUp(KeyCode::Scan(0x08)),
+                InputEvent::KeyUp(KeyCode::Scan(0x38)),
+                InputEvent::KeyUp(KeyCode::Scan(0x1D))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_caps_lock_typing() {
+        let layout = german_layout();
+        let typer = CharTyper::new(TypingStrategy::Layout, &layout, true);
+        assert_eq!(
+            typer.create_char_events('e', false),
+            vec![
+                InputEvent::KeyDown(KeyCode::Virtual(VK_SHIFT)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_E)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_E)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_SHIFT))
+            ]
+        );
+        assert_eq!(typer.create_char_events('E', false).len(), 2);
+        assert_eq!(typer.create_char_events('é', false).len(), 6);
+
+        let typer = CharTyper::new(TypingStrategy::Unicode, &layout, true);
+        assert_eq!(typer.create_char_events('e', false), vec![InputEvent::UnicodeDown('e' as u16), InputEvent::UnicodeUp('e' as u16)]);
+    }
+
+    #[test]
+    fn test_typing_profile() {
+        let profile = TypingProfile::new().delay(1, 3).hold(1, 2).whitespace_pause(5);
+        let keyboard = Keyboard::with_sink(RecordingSink::new()).profile(profile).seed(9);
+        let schedule = keyboard.schedule_keys("a b{shift down}c{shift up}").unwrap();
+        assert_eq!(schedule.len(), 10);
+        assert!((6..=8).contains(&schedule[4].get_delay()));
+        assert!(keyboard.get_pressed_keys().is_empty());
+
+        let keyboard = keyboard.seed(9);
+        keyboard.send_keys("a b{shift down}c{shift up}").unwrap();
+        let events: Vec<InputEvent> = schedule.iter().map(|e| e.get_event()).collect();
+        assert_eq!(keyboard.get_sink().get_events(), events);
+        assert_eq!(keyboard.schedule_keys("a b{shift down}c{shift up}").unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_record_mouse() {
+        let sink = RecordingSink::new().screen_size(800, 600);
+        let mouse = Mouse::with_sink(&sink).interval(0).move_time(0).holdkeys("{ctrl}");
+        mouse.click(Point::new(1000, 50)).unwrap();
+        mouse.right_click(Point::new(10, 20)).unwrap();
+        assert_eq!(
+            sink.get_events(),
+            vec![
+                InputEvent::Move(Point::new(799, 50)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_CONTROL)),
+                InputEvent::ButtonDown(MouseButton::Left),
+                InputEvent::ButtonUp(MouseButton::Left),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_CONTROL)),
+                InputEvent::Move(Point::new(10, 20)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_CONTROL)),
+                InputEvent::ButtonDown(MouseButton::Right),
+                InputEvent::ButtonUp(MouseButton::Right),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_CONTROL))
+            ]
+        );
+        assert_eq!(sink.get_cursor_pos().unwrap(), Point::new(10, 20));
+    }
+
+    #[test]
+    fn test_record_drag() {
+        let sink = RecordingSink::new();
+        let mouse = Mouse::with_sink(&sink).interval(0).move_time(0).hold_time(0).drop_time(0);
+        mouse.drag(Point::new(10, 10), Point::new(300, 200)).unwrap();
+        assert_eq!(
+            sink.get_events(),
+            vec![
+                InputEvent::Move(Point::new(10, 10)),
+                InputEvent::ButtonDown(MouseButton::Left),
+                InputEvent::Move(Point::new(300, 200)),
+                InputEvent::ButtonUp(MouseButton::Left)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hold_guard_unwind() {
+        let sink = RecordingSink::new();
+        let ret = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut guard = HoldGuard::new(&sink, 0);
+            guard.key_down(KeyCode::Virtual(VK_CONTROL)).unwrap();
+            guard.button_down(MouseButton::Left).unwrap();
+            panic!("interrupted");
+        }));
+        assert!(ret.is_err());
+        assert_eq!(
+            sink.get_events(),
+            vec![
+                InputEvent::KeyDown(KeyCode::Virtual(VK_CONTROL)),
+                InputEvent::ButtonDown(MouseButton::Left),
+                InputEvent::ButtonUp(MouseButton::Left),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_CONTROL))
+            ]
+        );
+    }
+
+    /// A sink which sends the first `limit` events of all the batches and then fails once.
+    struct PartialSink {
+        sink: RecordingSink,
+        limit: Cell<usize>
+    }
+
+    impl InputSink for PartialSink {
+        fn send(&self, events: &[InputEvent]) -> Result<()> {
+            let limit = self.limit.get();
+            if events.len() > limit {
+                self.limit.set(usize::MAX);
+                self.sink.send(&events[..limit])?;
+                Err(Error::from("send failed"))
+            } else {
+                self.limit.set(limit - events.len());
+                self.sink.send(events)
+            }
+        }
+
+        fn get_cursor_pos(&self) -> Result<Point> {
+            self.sink.get_cursor_pos()
+        }
+
+        fn get_screen_size(&self) -> Result<(i32, i32)> {
+            self.sink.get_screen_size()
+        }
+
+        fn get_keyboard_layout(&self) -> KeyboardLayout {
+            self.sink.get_keyboard_layout()
+        }
+
+        fn is_key_toggled(&self, key: VIRTUAL_KEY) -> bool {
+            self.sink.is_key_toggled(key)
+        }
+    }
+
+    #[test]
+    fn test_release_after_partial_send() {
+        let sink = PartialSink {
+            sink: RecordingSink::new(),
+            limit: Cell::new(1)
+        };
+        let keyboard = Keyboard::with_sink(&sink).warn_modifiers(false);
+        assert!(keyboard.send_keys("{shift down}{ctrl down}{end}").is_err());
+        assert_eq!(
+            sink.sink.get_events(),
+            vec![
+                InputEvent::KeyDown(KeyCode::Virtual(VK_SHIFT)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_END)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_CONTROL)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_SHIFT))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hold_guard_release_failure() {
+        let sink = PartialSink {
+            sink: RecordingSink::new(),
+            limit: Cell::new(2)
+        };
+        let mut guard = HoldGuard::new(&sink, 0);
+        guard.key_down(KeyCode::Virtual(VK_CONTROL)).unwrap();
+        guard.button_down(MouseButton::Left).unwrap();
+        assert!(guard.button_up(MouseButton::Left).is_err());
+        drop(guard);
+        assert_eq!(
+            sink.sink.get_events(),
+            vec![
+                InputEvent::KeyDown(KeyCode::Virtual(VK_CONTROL)),
+                InputEvent::ButtonDown(MouseButton::Left),
+                InputEvent::ButtonUp(MouseButton::Left),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_CONTROL))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_holdkeys_click() {
+        let sink = RecordingSink::new();
+        let mouse = Mouse::with_sink(&sink).interval(0).move_time(0).holdkeys("{ctrl}{shift}");
+        mouse.click(Point::new(10, 10)).unwrap();
+        assert_eq!(
+            sink.get_events(),
+            vec![
+                InputEvent::Move(Point::new(10, 10)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_CONTROL)),
+                InputEvent::KeyDown(KeyCode::Virtual(VK_SHIFT)),
+                InputEvent::ButtonDown(MouseButton::Left),
+                InputEvent::ButtonUp(MouseButton::Left),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_SHIFT)),
+                InputEvent::KeyUp(KeyCode::Virtual(VK_CONTROL))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_release_all_modifiers() {
+        let sink = RecordingSink::new();
+        sink.send(&[InputEvent::KeyDown(KeyCode::Virtual(VK_RCONTROL)), InputEvent::KeyDown(KeyCode::Virtual(VK_SHIFT))]).unwrap();
+
+        let keyboard = Keyboard::with_sink(&sink);
+        assert_eq!(keyboard.release_all_modifiers().unwrap(), vec![VK_SHIFT, VK_RCONTROL]);
+        assert!(!sink.is_key_pressed(VK_SHIFT));
+        assert!(keyboard.release_all_modifiers().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_escape_round_trip() {
+        for text in ["", "abc", "{enter}", "{Hi,(rust)}", "}{)(", "{{}}", "a{b}c(d)e", "中文{}(测试)", "{VK 0x5D}{shift down}"] {
+            let escaped = escape_keys(text);
+            let expr = KeyExpression::parse(&escaped).unwrap();
+            let chars: String = expr.get_items().iter().map(|item| match item {
+                KeyItem::Text(text) => text.clone(),
+                _ => panic!("unexpected item {:?} in {}", item, escaped)
+            }).collect();
+            assert_eq!(chars, text);
+            assert_eq!(expr.to_string(), escaped);
+        }
+    }
+
+    #[test]
+    fn test_type_text() {
+        let text = "{enter}(a)";
+        let typed = RecordingSink::new();
+        Keyboard::with_sink(&typed).type_text(text).unwrap();
+
+        let sent = RecordingSink::new();
+        Keyboard::with_sink(&sent).send_keys(&escape_keys(text)).unwrap();
+
+        assert_eq!(typed.get_events(), sent.get_events());
+        assert!(!typed.get_events().contains(&InputEvent::KeyDown(KeyCode::Virtual(VK_RETURN))));
+    }
+
+    #[test]
+    fn test_normalize_locks() {
+        let sink = RecordingSink::new().toggled(VK_CAPITAL);
+        let keyboard = Keyboard::with_sink(&sink).normalize_locks(true).warn_modifiers(false);
+        keyboard.send_keys("a").unwrap();
+
+        let events = sink.get_events();
+        let capital = [InputEvent::KeyDown(KeyCode::Virtual(VK_CAPITAL)), InputEvent::KeyUp(KeyCode::Virtual(VK_CAPITAL))];
+        assert_eq!(events[..2], capital);
+        assert_eq!(events[events.len() - 2..], capital);
+        assert!(!events[2..events.len() - 2].iter().any(|e| capital.contains(e)));
+        assert!(sink.is_key_toggled(VK_CAPITAL));
+        assert_eq!(KeyboardState::with_sink(&sink).get_toggled_locks(), vec![VK_CAPITAL]);
+    }
+
+    #[test]
+    fn test_keyboard_state() {
+        let sink = RecordingSink::new().toggled(VK_NUMLOCK);
+        sink.send(&[InputEvent::KeyDown(KeyCode::Virtual(VK_LCONTROL)), InputEvent::KeyDown(KeyCode::Virtual(VK_A))]).unwrap();
+
+        let state = KeyboardState::with_sink(&sink);
+        assert!(state.is_pressed(VK_A));
+        assert!(state.is_toggled(VK_NUMLOCK));
+        assert!(!state.is_toggled(VK_CAPITAL));
+        assert_eq!(state.get_pressed_modifiers(), vec![VK_LCONTROL]);
+    }
+
+    #[test]
+    fn test_keyboard_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<Keyboard>();
+        assert_sync::<Mouse>();
+    }
+
+    #[test]
+    fn test_held_modifiers() {
+        let sink = RecordingSink::new();
+        sink.send(&[InputEvent::KeyDown(KeyCode::Virtual(VK_LMENU))]).unwrap();
+
+        let keyboard = Keyboard::with_sink(&sink);
+        assert_eq!(keyboard.get_held_modifiers(), vec![VK_LMENU]);
+    }
+
+    #[test]
+    fn test_message_params() {
+        assert_eq!(create_key_lparam(0x1E, false, false, false), 0x001E0001);
+        assert_eq!(create_key_lparam(0x1E, false, false, true), 0xC01E0001_u32 as isize);
+        assert_eq!(create_key_lparam(0x4B, true, true, false), 0x214B0001);
+        assert_eq!(create_point_lparam(Point::new(10, 20)), 0x0014000A);
+        assert_eq!(create_point_lparam(Point::new(-1, 2)), 0x0002FFFF);
+        assert_eq!(get_button_message(MouseButton::Left, true, false), WM_LBUTTONDOWN);
+        assert_eq!(get_button_message(MouseButton::Left, true, true), WM_LBUTTONDBLCLK);
+        assert_eq!(get_button_message(MouseButton::X2, false, false), WM_XBUTTONUP);
+        assert!(is_extended_key(VK_RIGHT));
+        assert!(!is_extended_key(VK_A));
+    }
+
+    #[test]
+    fn test_record_trajectory() {
+        let trajectory = Trajectory::new(TrajectoryProfile::Bezier).jitter(1);
+        let sink = RecordingSink::new();
+        let mouse = Mouse::with_sink(&sink).move_time(0).trajectory(trajectory).seed(11);
+        mouse.move_to(Point::new(300, 200)).unwrap();
+
+        let points = trajectory.generate(Point::new(0, 0), Point::new(300, 200), 0, &mut create_rng(Some(11)));
+        let moves: Vec<InputEvent> = points.iter().map(|p| InputEvent::Move(p.get_point())).collect();
+        assert_eq!(sink.get_events(), moves);
+        assert_eq!(sink.get_cursor_pos().unwrap(), Point::new(300, 200));
+    }
+
+    #[test]
+    fn test_record_monitors() {
+        let sink = RecordingSink::new().monitors(&[Rect::new(0, 0, 1920, 1080), Rect::new(-1280, 0, 0, 1024)]);
+        let mouse = Mouse::with_sink(&sink).move_time(0).absolute(true);
+        mouse.move_to(Point::new(-100, 2000)).unwrap();
+        mouse.move_to(Point::new(-640, 500)).unwrap();
+        assert_eq!(
+            sink.get_events(),
+            vec![
+                InputEvent::MoveAbsolute(Point::new(0, 1079)),
+                InputEvent::MoveAbsolute(Point::new(-640, 500))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_wheel() {
+        let sink = RecordingSink::new();
+        let mouse = Mouse::with_sink(&sink).interval(0).move_time(0).auto_move(false);
+        mouse.scroll_wheel(Point::new(10, 10), -3).unwrap();
+        mouse.scroll_hwheel(Point::new(10, 10), 2).unwrap();
+        mouse.press(MouseButton::X1).unwrap();
+        mouse.release(MouseButton::X1).unwrap();
+        assert_eq!(
+            sink.get_events(),
+            vec![
+                InputEvent::Wheel(-360),
+                InputEvent::HorizontalWheel(240),
+                InputEvent::ButtonDown(MouseButton::X1),
+                InputEvent::ButtonUp(MouseButton::X1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_expression() {
+        let expr = KeyExpression::parse("Hi{ctrl}({(}b){enter}").unwrap();
+        assert_eq!(expr.get_items().len(), 4);
+        assert_eq!(expr.get_items()[0], KeyItem::Text("Hi".into()));
+        assert!(matches!(expr.get_items()[1], KeyItem::HoldKey(ref key) if key.get_key() == VK_CONTROL));
+        assert_eq!(expr.get_items()[2], KeyItem::Group(vec![KeyItem::Text("(b".into())]));
+        assert!(matches!(expr.get_items()[3], KeyItem::Key(ref key) if key.get_name() == "ENTER"));
+    }
+
+    #[test]
+    fn test_parse_expression_error() {
+        let err = KeyExpression::parse("Hello{tab}{Entr}World").unwrap_err();
+        assert_eq!(err.get_offset(), 10);
+        assert_eq!(err.get_token(), "{Entr}");
+
+        let err = KeyExpression::parse("你好{enter").unwrap_err();
+        assert_eq!(err.get_offset(), 2);
+        assert_eq!(err.get_token(), "{enter");
+
+        let err = KeyExpression::parse("ab(cd").unwrap_err();
+        assert_eq!(err.get_offset(), 2);
+        assert_eq!(err.get_token(), "(");
+
+        assert!(parse_input("{Entr}").is_err());
+    }
+
+    #[test]
+    fn test_expression_round_trip() {
+        let sources = ["{ctrl}c", "{Shift}(ab{home})x", "{TAB 5}{down*15}{{ 2}", "{shift down}(a{ctrl down})b{shift up}", "{vk 93}{SC 0xE05B 2}{sc 28 down}", "{{}Hi,{(}rust!{)}{}}", "你好！{enter}", "a)b}c", "{alt}((a)(b))"];
+        for source in sources {
+            let expr = KeyExpression::parse(source).unwrap();
+            let text = expr.to_string();
+            assert_eq!(KeyExpression::parse(&text).unwrap(), expr, "{} -> {}", source, text);
+            assert_eq!(parse_input(&text).unwrap(), parse_input(source).unwrap());
         }
     }
 }
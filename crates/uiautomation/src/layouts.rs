@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
+use windows::Win32::UI::TextServices::HKL;
+use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+use windows::Win32::UI::WindowsAndMessaging::GetWindowThreadProcessId;
+use windows::Win32::UI::WindowsAndMessaging::MAPVK_VK_TO_VSC;
+
+/// The shift key is pressed in a keystroke.
+pub const MODIFIER_SHIFT: u8 = 0x01;
+/// The ctrl key is pressed in a keystroke.
+pub const MODIFIER_CTRL: u8 = 0x02;
+/// The alt key is pressed in a keystroke. `Ctrl+Alt` is the `AltGr` key.
+pub const MODIFIER_ALT: u8 = 0x04;
+/// The windows key is pressed in a hotkey. It is not used by keyboard layouts.
+pub const MODIFIER_WIN: u8 = 0x08;
+
+/// The captured keyboard layouts by their `HKL` handles. Capturing a layout translates thousands of keystrokes,
+/// so each layout is captured once in the process.
+static CAPTURED_LAYOUTS: Mutex<Vec<(isize, KeyboardLayout)>> = Mutex::new(Vec::new());
+
+/// The keystroke which types a character in a keyboard layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyStroke {
+    key: VIRTUAL_KEY,
+    scan: u16,
+    modifiers: u8
+}
+
+impl KeyStroke {
+    /// Creates a keystroke of the virtual `key`, its `scan` code and the `modifiers` bit set.
+    pub fn new(key: VIRTUAL_KEY, scan: u16, modifiers: u8) -> Self {
+        Self {
+            key,
+            scan,
+            modifiers
+        }
+    }
+
+    /// Retrieves the virtual key.
+    pub fn get_key(&self) -> VIRTUAL_KEY {
+        self.key
+    }
+
+    /// Retrieves the scan code of the key. `0` means unknown.
+    pub fn get_scan_code(&self) -> u16 {
+        self.scan
+    }
+
+    /// Retrieves the modifiers bit set, which combines `MODIFIER_SHIFT`, `MODIFIER_CTRL` and `MODIFIER_ALT`.
+    pub fn get_modifiers(&self) -> u8 {
+        self.modifiers
+    }
+
+    /// Determines whether the shift key is pressed.
+    pub fn has_shift(&self) -> bool {
+        (self.modifiers & MODIFIER_SHIFT) != 0
+    }
+}
+
+/// A character-to-keystroke table of a keyboard layout.
+/// 
+/// A character is typed either by a single keystroke, or by a dead key followed by another keystroke, such as `´` + `e` for `é`.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use uiautomation::layouts::KeyStroke;
+/// use uiautomation::layouts::KeyboardLayout;
+/// use uiautomation::layouts::MODIFIER_ALT;
+/// use uiautomation::layouts::MODIFIER_CTRL;
+/// use windows::Win32::UI::Input::KeyboardAndMouse::*;
+/// 
+/// // A part of the German layout.
+/// let layout = KeyboardLayout::new()
+///     .key('e', KeyStroke::new(VK_E, 0x12, 0))
+///     .key('@', KeyStroke::new(VK_Q, 0x10, MODIFIER_CTRL | MODIFIER_ALT))
+///     .dead_key('é', KeyStroke::new(VK_OEM_6, 0x0D, 0), KeyStroke::new(VK_E, 0x12, 0));
+/// 
+/// assert_eq!(layout.get_strokes('@').unwrap().len(), 1);
+/// assert_eq!(layout.get_strokes('é').unwrap().len(), 2);
+/// assert!(layout.get_strokes('€').is_none());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyboardLayout {
+    keys: HashMap<char, KeyStroke>,
+    dead_keys: HashMap<char, (KeyStroke, KeyStroke)>
+}
+
+impl KeyboardLayout {
+    /// Creates an empty layout, which types all the characters as unicode.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Retrieves the keyboard layout of the foreground window, including the dead keys.
+    /// 
+    /// The layout is captured when the foreground window uses it for the first time, and cached by its `HKL` handle.
+    pub fn current() -> Self {
+        let layout = unsafe {
+            let thread = GetWindowThreadProcessId(GetForegroundWindow(), std::ptr::null_mut());
+            GetKeyboardLayout(thread)
+        };
+        Self::cached(layout)
+    }
+
+    /// Retrieves the keyboard layout of the `HKL` handle, which is captured by `capture()` only once in the process.
+    pub fn cached(layout: HKL) -> Self {
+        if let Ok(layouts) = CAPTURED_LAYOUTS.lock() {
+            if let Some((_, table)) = layouts.iter().find(|(handle, _)| *handle == layout.0) {
+                return table.clone();
+            }
+        }
+
+        let table = Self::capture(layout);
+        if let Ok(mut layouts) = CAPTURED_LAYOUTS.lock() {
+            if !layouts.iter().any(|(handle, _)| *handle == layout.0) {
+                layouts.push((layout.0, table.clone()));
+            }
+        }
+
+        table
+    }
+
+    /// Captures the keyboard layout of the `HKL` handle, including the dead keys.
+    pub fn capture(layout: HKL) -> Self {
+        const STATES: [u8; 4] = [0, MODIFIER_SHIFT, MODIFIER_CTRL | MODIFIER_ALT, MODIFIER_SHIFT | MODIFIER_CTRL | MODIFIER_ALT];
+
+        let mut table = Self::new();
+        let mut dead_strokes: Vec<KeyStroke> = Vec::new();
+        for key in capture_order() {
+            if is_modifier_key(key) {
+                continue;
+            }
+
+            let scan = unsafe { MapVirtualKeyExW(key.0 as _, MAPVK_VK_TO_VSC, layout) } as u16;
+            for modifiers in STATES {
+                let stroke = KeyStroke::new(key, scan, modifiers);
+                match to_unicode(&stroke, layout, false) {
+                    Translation::Char(ch) => {
+                        table.keys.entry(ch).or_insert(stroke);
+                    },
+                    Translation::Dead => {
+                        dead_strokes.push(stroke);
+                    },
+                    Translation::None => ()
+                }
+            }
+        }
+
+        for (ch, key) in [('\t', VK_TAB), ('\n', VK_RETURN), ('\r', VK_RETURN)] {
+            let scan = unsafe { MapVirtualKeyExW(key.0 as _, MAPVK_VK_TO_VSC, layout) } as u16;
+            table.keys.entry(ch).or_insert(KeyStroke::new(key, scan, 0));
+        }
+
+        let mut bases: Vec<KeyStroke> = table.keys.values().copied().collect();
+        bases.sort_by_key(|s| (s.modifiers, s.key.0));
+        for dead in dead_strokes {
+            for base in &bases {
+                to_unicode(&dead, layout, true);
+                if let Translation::Char(ch) = to_unicode(base, layout, false) {
+                    if !table.keys.contains_key(&ch) {
+                        table.dead_keys.entry(ch).or_insert((dead, *base));
+                    }
+                }
+            }
+        }
+
+        table
+    }
+
+    /// Adds a character which is typed by a single keystroke.
+    pub fn key(mut self, ch: char, stroke: KeyStroke) -> Self {
+        self.keys.insert(ch, stroke);
+        self
+    }
+
+    /// Adds a character which is typed by the `dead` key followed by the `base` keystroke.
+    pub fn dead_key(mut self, ch: char, dead: KeyStroke, base: KeyStroke) -> Self {
+        self.dead_keys.insert(ch, (dead, base));
+        self
+    }
+
+    /// Determines whether the layout has no characters.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.dead_keys.is_empty()
+    }
+
+    /// Retrieves the keystrokes which type the character `ch`, or `None` if the layout can not type it.
+    pub fn get_strokes(&self, ch: char) -> Option<Vec<KeyStroke>> {
+        if let Some(stroke) = self.keys.get(&ch) {
+            Some(vec![*stroke])
+        } else {
+            self.dead_keys.get(&ch).map(|(dead, base)| vec![*dead, *base])
+        }
+    }
+}
+
+enum Translation {
+    None,
+    Char(char),
+    Dead
+}
+
+/// Retrieves the virtual keys in the capturing order. The numpad keys come last, so they only type the characters missing in the main block,
+/// because the numpad `.` is the `Delete` key when the num lock is off.
+fn capture_order() -> impl Iterator<Item = VIRTUAL_KEY> {
+    (0x08..VK_NUMPAD0.0).chain(VK_F1.0..0xFF).chain(VK_NUMPAD0.0..VK_F1.0).map(VIRTUAL_KEY)
+}
+
+fn is_modifier_key(key: VIRTUAL_KEY) -> bool {
+    matches!(key, VK_SHIFT | VK_CONTROL | VK_MENU | VK_LSHIFT | VK_RSHIFT | VK_LCONTROL | VK_RCONTROL | VK_LMENU | VK_RMENU | VK_CAPITAL | VK_NUMLOCK | VK_SCROLL)
+}
+
+/// Translates the keystroke by the layout. A dead key stays in the keyboard buffer if `keep_dead` is `true`.
+fn to_unicode(stroke: &KeyStroke, layout: HKL, keep_dead: bool) -> Translation {
+    let mut state = [0u8; 256];
+    if (stroke.modifiers & MODIFIER_SHIFT) != 0 {
+        state[VK_SHIFT.0 as usize] = 0x80;
+    }
+    if (stroke.modifiers & MODIFIER_CTRL) != 0 {
+        state[VK_CONTROL.0 as usize] = 0x80;
+    }
+    if (stroke.modifiers & MODIFIER_ALT) != 0 {
+        state[VK_MENU.0 as usize] = 0x80;
+    }
+
+    let mut buffer = [0u16; 8];
+    let ret = unsafe { ToUnicodeEx(stroke.key.0 as _, stroke.scan as _, &state, &mut buffer, 0, layout) };
+    if ret < 0 {
+        if !keep_dead {
+            // clears the dead key from the keyboard buffer
+            let space = [0u8; 256];
+            unsafe { ToUnicodeEx(VK_SPACE.0 as _, 0x39, &space, &mut buffer, 0, layout) };
+        }
+        Translation::Dead
+    } else if ret == 1 && buffer[0] >= 0x20 && buffer[0] != 0x7F {
+        char::from_u32(buffer[0] as u32).map_or(Translation::None, Translation::Char)
+    } else {
+        Translation::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+    use crate::layouts::KeyboardLayout;
+    use crate::layouts::capture_order;
+
+    #[test]
+    fn test_capture_order() {
+        let keys: Vec<VIRTUAL_KEY> = capture_order().collect();
+        assert_eq!(keys.len(), 0xFF - 0x08);
+
+        let position = |key: VIRTUAL_KEY| keys.iter().position(|k| *k == key).unwrap();
+        assert!(position(VK_OEM_PERIOD) < position(VK_DECIMAL));
+        assert!(position(VK_OEM_PLUS) < position(VK_ADD));
+        assert!(position(VK_OEM_MINUS) < position(VK_SUBTRACT));
+        assert!(position(VK_OEM_2) < position(VK_DIVIDE));
+    }
+
+    #[test]
+    fn test_capture_period() {
+        let layout = KeyboardLayout::current();
+        let strokes = layout.get_strokes('.').unwrap();
+        assert_eq!(strokes[0].get_key(), VK_OEM_PERIOD);
+    }
+}
@@ -6,8 +6,17 @@ pub mod patterns;
 pub mod filters;
 pub mod controls;
 pub mod actions;
+pub mod events;
+pub mod screens;
+pub mod layouts;
 pub mod inputs;
+pub mod hotkeys;
+pub mod chains;
+pub mod typing;
+pub mod trajectory;
 pub mod processes;
+pub mod applications;
+pub mod clipboard;
 
 pub use self::errors::Error;
 pub use self::errors::Result;
@@ -1,33 +1,99 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
 use std::mem;
+use std::thread::sleep;
+use std::time::Duration;
+use std::time::Instant;
 
+use windows::Win32::Foundation::BOOL;
 use windows::Win32::Foundation::CloseHandle;
+use windows::Win32::Foundation::DUPLICATE_SAME_ACCESS;
+use windows::Win32::Foundation::DuplicateHandle;
+use windows::Win32::Foundation::FILETIME;
+use windows::Win32::Foundation::HANDLE;
+use windows::Win32::Foundation::WAIT_ABANDONED;
 use windows::Win32::Foundation::WAIT_FAILED;
 use windows::Win32::Foundation::WAIT_OBJECT_0;
 use windows::Win32::Foundation::WAIT_TIMEOUT;
+use windows::Win32::Security::SECURITY_ATTRIBUTES;
+use windows::Win32::Storage::FileSystem::CREATE_ALWAYS;
+use windows::Win32::Storage::FileSystem::CreateFileW;
+use windows::Win32::Storage::FileSystem::FILE_ATTRIBUTE_NORMAL;
+use windows::Win32::Storage::FileSystem::FILE_GENERIC_READ;
+use windows::Win32::Storage::FileSystem::FILE_GENERIC_WRITE;
+use windows::Win32::Storage::FileSystem::FILE_SHARE_READ;
+use windows::Win32::Storage::FileSystem::FILE_SHARE_WRITE;
+use windows::Win32::Storage::FileSystem::OPEN_EXISTING;
+use windows::Win32::System::Console::GetStdHandle;
+use windows::Win32::System::Console::STD_ERROR_HANDLE;
+use windows::Win32::System::Console::STD_HANDLE;
+use windows::Win32::System::Console::STD_INPUT_HANDLE;
+use windows::Win32::System::Console::STD_OUTPUT_HANDLE;
+use windows::Win32::System::Diagnostics::ToolHelp::CreateToolhelp32Snapshot;
+use windows::Win32::System::Diagnostics::ToolHelp::PROCESSENTRY32W;
+use windows::Win32::System::Diagnostics::ToolHelp::Process32FirstW;
+use windows::Win32::System::Diagnostics::ToolHelp::Process32NextW;
+use windows::Win32::System::Diagnostics::ToolHelp::TH32CS_SNAPPROCESS;
+use windows::Win32::System::JobObjects::AssignProcessToJobObject;
+use windows::Win32::System::JobObjects::CreateJobObjectW;
+use windows::Win32::System::JobObjects::JOBOBJECT_BASIC_PROCESS_ID_LIST;
+use windows::Win32::System::JobObjects::JOBOBJECT_EXTENDED_LIMIT_INFORMATION;
+use windows::Win32::System::JobObjects::JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+use windows::Win32::System::JobObjects::JobObjectBasicProcessIdList;
+use windows::Win32::System::JobObjects::JobObjectExtendedLimitInformation;
+use windows::Win32::System::JobObjects::QueryInformationJobObject;
+use windows::Win32::System::JobObjects::SetInformationJobObject;
+use windows::Win32::System::JobObjects::TerminateJobObject;
+use windows::Win32::System::RemoteDesktop::ProcessIdToSessionId;
+use windows::Win32::System::Threading::CREATE_SUSPENDED;
+use windows::Win32::System::Threading::CREATE_UNICODE_ENVIRONMENT;
 use windows::Win32::System::Threading::CreateProcessW;
+use windows::Win32::System::Threading::GetCurrentProcess;
 use windows::Win32::System::Threading::GetExitCodeProcess;
+use windows::Win32::System::Threading::GetProcessTimes;
+use windows::Win32::System::Threading::OpenProcess;
 use windows::Win32::System::Threading::PROCESS_CREATION_FLAGS;
+use windows::Win32::System::Threading::QueryFullProcessImageNameW;
+use windows::Win32::System::Threading::ResumeThread;
 use windows::Win32::System::Threading::PROCESS_INFORMATION;
+use windows::Win32::System::Threading::PROCESS_NAME_WIN32;
+use windows::Win32::System::Threading::PROCESS_QUERY_LIMITED_INFORMATION;
+use windows::Win32::System::Threading::PROCESS_SYNCHRONIZE;
+use windows::Win32::System::Threading::PROCESS_TERMINATE;
+use windows::Win32::System::Threading::STARTF_USESHOWWINDOW;
+use windows::Win32::System::Threading::STARTF_USESTDHANDLES;
 use windows::Win32::System::Threading::STARTUPINFOW;
 use windows::Win32::System::Threading::TerminateProcess;
+use windows::Win32::System::Threading::WaitForInputIdle;
 use windows::Win32::System::Threading::WaitForSingleObject;
+use windows::Win32::UI::Accessibility::TreeScope_Children;
+use windows::Win32::UI::Accessibility::UIA_ProcessIdPropertyId;
+use windows::Win32::UI::WindowsAndMessaging::SHOW_WINDOW_CMD;
 use windows::core::PCWSTR;
 use windows::core::PWSTR;
 
 use super::Error;
 use super::Result;
+use super::errors::ERR_ABANDONED;
 use super::errors::ERR_NONE;
+use super::errors::ERR_NOTFOUND;
 use super::errors::ERR_TIMEOUT;
+use super::core::UIAutomation;
+use super::core::UIElement;
+use super::variants::Variant;
 
 /// Windows process wrapper.
 #[derive(Debug)]
 pub struct Process {
-    information: PROCESS_INFORMATION
+    information: PROCESS_INFORMATION,
+    job: Option<Job>
 }
 
 impl Process {
     /// Create process by command line.
     /// 
+    /// Use `ProcessBuilder` to create process with quoted arguments, working directory, environment or redirected stdio.
+    /// 
     /// # Examples
     /// ```
     /// use uiautomation::processes::Process;
@@ -54,13 +120,81 @@ impl Process {
 
         if ret.as_bool() {
             Ok(Self {
-                information
+                information,
+                job: None
             })
         } else {
             Err(Error::last_os_error())
         }
     }
 
+    /// Open the running process by the process `id`.
+    /// 
+    /// The process is opened with the rights to query, wait for and terminate it, or only to query and wait for it if termination is denied.
+    pub fn from_pid(id: u32) -> Result<Self> {
+        let handle = unsafe {
+            OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION | PROCESS_SYNCHRONIZE | PROCESS_TERMINATE, false, id)
+                .or_else(|_| OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION | PROCESS_SYNCHRONIZE, false, id))?
+        };
+
+        Ok(Self {
+            information: PROCESS_INFORMATION {
+                hProcess: handle,
+                dwProcessId: id,
+                ..Default::default()
+            },
+            job: None
+        })
+    }
+
+    /// Open the process which owns the `element`.
+    pub fn from_element(element: &UIElement) -> Result<Self> {
+        let id = element.get_process_id()?;
+        Self::from_pid(id as _)
+    }
+
+    /// Open all the running processes whose image name is `name`, such as `EXCEL.EXE`. The name is case-insensitive.
+    /// 
+    /// # Examples
+    /// ```
+    /// use uiautomation::processes::Process;
+    /// 
+    /// let explorers = Process::find_by_name("explorer.exe").unwrap();
+    /// assert!(!explorers.is_empty());
+    /// ```
+    pub fn find_by_name(name: &str) -> Result<Vec<Self>> {
+        let processes: Vec<Self> = Self::enumerate()?.iter()
+            .filter(|info| info.get_name().eq_ignore_ascii_case(name))
+            .filter_map(|info| info.open().ok())
+            .collect();
+
+        if processes.is_empty() {
+            Err(Error::new(ERR_NOTFOUND, "Process not found"))
+        } else {
+            Ok(processes)
+        }
+    }
+
+    /// Enumerate all the running processes.
+    pub fn enumerate() -> Result<Vec<ProcessInfo>> {
+        let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0)? };
+
+        let mut processes: Vec<ProcessInfo> = Vec::new();
+        let mut entry = PROCESSENTRY32W {
+            dwSize: mem::size_of::<PROCESSENTRY32W>() as _,
+            ..Default::default()
+        };
+        let mut found = unsafe { Process32FirstW(snapshot, &mut entry) }.as_bool();
+        while found {
+            processes.push(ProcessInfo::new(&entry));
+            found = unsafe { Process32NextW(snapshot, &mut entry) }.as_bool();
+        }
+
+        unsafe { CloseHandle(snapshot) };
+
+        Ok(processes)
+    }
+
     #[inline]
     fn startupinfo() -> STARTUPINFOW {
         let mut si = STARTUPINFOW::default();
@@ -85,21 +219,192 @@ impl Process {
     /// 
     /// `timeout` is the milliseconds to wait for.
     pub fn wait_for(&self, timeout: u32) -> Result<()> {
+        if self.wait(timeout)? {
+            Ok(())
+        } else {
+            Err(Error::new(ERR_TIMEOUT, "Wait Timeout"))
+        }
+    }
+
+    /// Wait for the process to exit in `timeout`, and return the exit status, or `None` if the process is still running.
+    /// 
+    /// The timeout is limited to about 49 days.
+    /// 
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use uiautomation::processes::ProcessBuilder;
+    /// 
+    /// let cmd = ProcessBuilder::new("cmd.exe").args(&["/c", "exit", "3"]).spawn().unwrap();
+    /// let status = cmd.wait_timeout(Duration::from_secs(5)).unwrap();
+    /// assert_eq!(status.map(|s| s.get_code()), Some(3));
+    /// ```
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<Option<ExitStatus>> {
+        // `u32::MAX` means infinite.
+        let timeout = timeout.as_millis().min(u32::MAX as u128 - 1) as u32;
+        if self.wait(timeout)? {
+            let code = self.get_exit_code()?;
+            Ok(Some(ExitStatus::new(code)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Determine whether the process is still running.
+    pub fn is_running(&self) -> Result<bool> {
+        let exited = self.wait(0)?;
+        Ok(!exited)
+    }
+
+    /// Wait for the process to exit asynchronously.
+    /// 
+    /// The returned future waits by a wait handle registered in the system thread pool, without blocking a thread.
+    #[cfg(feature = "async")]
+    pub fn wait_async(&self) -> Result<ProcessWait<'_>> {
+        ProcessWait::new(self)
+    }
+
+    /// Wait for the process to exit in `timeout` milliseconds, and return whether it has exited.
+    fn wait(&self, timeout: u32) -> Result<bool> {
         let ret = unsafe {
             WaitForSingleObject(self.information.hProcess, timeout)
         };
 
         if ret == WAIT_OBJECT_0.0 {
-            Ok(())
+            Ok(true)
+        } else if ret == WAIT_TIMEOUT.0 {
+            Ok(false)
         } else if ret == WAIT_FAILED.0 {
             Err(Error::last_os_error())
+        } else if ret == WAIT_ABANDONED.0 {
+            Err(Error::new(ERR_ABANDONED, "Wait Abandoned"))
+        } else {
+            Err(Error::new(ERR_NONE, &format!("Unexpected Wait Result: {:#X}", ret)))
+        }
+    }
+
+    /// Exit the process and all its descendant processes with `exit_code` by force, and return the killed processes.
+    /// 
+    /// The process is killed first, then its descendants are killed from parent to child.
+    /// If the process is created in a job by `ProcessBuilder::kill_on_close()`, the processes in the job are killed too,
+    /// including the ones whose parent has exited. The processes which fail to be killed are left out of the result.
+    pub fn terminate_tree(&self, exit_code: u32) -> Result<Vec<ProcessInfo>> {
+        let processes = Self::enumerate()?;
+        let id = self.get_id();
+
+        let mut targets = get_descendants(id, get_creation_time(self.information.hProcess), &processes);
+        if let Some(ref job) = self.job {
+            for job_id in job.get_process_ids()? {
+                if job_id != id && !targets.iter().any(|p| p.id == job_id) {
+                    if let Some(info) = processes.iter().find(|p| p.id == job_id) {
+                        targets.push(info);
+                    }
+                }
+            }
+        }
+
+        let mut killed: Vec<ProcessInfo> = Vec::new();
+        if self.terminate(exit_code).is_ok() {
+            if let Some(info) = processes.iter().find(|p| p.id == id) {
+                killed.push(info.clone());
+            }
+        }
+
+        for info in targets {
+            if let Ok(process) = info.open() {
+                // the process id may be reused after the snapshot.
+                if get_creation_time(process.information.hProcess) == info.creation_time && process.terminate(exit_code).is_ok() {
+                    killed.push(info.clone());
+                }
+            }
+        }
+
+        if let Some(ref job) = self.job {
+            // the processes are already killed one by one, so the killed list is returned even if the job fails.
+            let _ = job.terminate(exit_code);
+        }
+
+        Ok(killed)
+    }
+
+    /// Get the id of the process.
+    pub fn get_id(&self) -> u32 {
+        self.information.dwProcessId
+    }
+
+    /// Get the job which the process is created in.
+    pub fn get_job(&self) -> Option<&Job> {
+        self.job.as_ref()
+    }
+
+    /// Wait for the process to finish its initialization and wait for user input.
+    /// 
+    /// `timeout` is the milliseconds to wait for. The process without a message loop, such as a console application, fails immediately.
+    pub fn wait_for_input_idle(&self, timeout: u32) -> Result<()> {
+        let ret = unsafe {
+            WaitForInputIdle(self.information.hProcess, timeout)
+        };
+
+        if ret == 0 {
+            Ok(())
         } else if ret == WAIT_TIMEOUT.0 {
-            Err(Error::new(ERR_TIMEOUT, "Wait Timeout"))
+            Err(Error::new(ERR_TIMEOUT, "Wait Input Idle Timeout"))
         } else {
-            Err(Error::new(ERR_NONE, "Wait Failed"))
+            Err(Error::last_os_error())
+        }
+    }
+
+    /// Get all the top-level windows of the process.
+    pub fn windows(&self) -> Result<Vec<UIElement>> {
+        let automation = UIAutomation::new()?;
+        self.find_windows(&automation)
+    }
+
+    /// Wait for the main window of the process, which is the first visible top-level window.
+    /// 
+    /// `timeout` is the milliseconds to wait for.
+    /// 
+    /// # Examples
+    /// ```
+    /// use uiautomation::processes::Process;
+    /// 
+    /// let notepad = Process::create("notepad.exe").unwrap();
+    /// let window = notepad.main_window(5000).unwrap();
+    /// println!("{}", window.get_name().unwrap());
+    /// notepad.terminate(0).unwrap();
+    /// ```
+    pub fn main_window(&self, timeout: u32) -> Result<UIElement> {
+        let automation = UIAutomation::new()?;
+        self.find_main_window(&automation, timeout)
+    }
+
+    pub(crate) fn find_main_window(&self, automation: &UIAutomation, timeout: u32) -> Result<UIElement> {
+        let start = Instant::now();
+        loop {
+            let windows = self.find_windows(automation)?;
+            if let Some(window) = windows.into_iter().find(|w| w.is_offscreen().is_ok_and(|offscreen| !offscreen)) {
+                return Ok(window);
+            }
+
+            if start.elapsed() >= Duration::from_millis(timeout as _) {
+                return Err(Error::new(ERR_TIMEOUT, "Wait Main Window Timeout"));
+            }
+
+            sleep(Duration::from_millis(100));
         }
     }
 
+    pub(crate) fn find_windows(&self, automation: &UIAutomation) -> Result<Vec<UIElement>> {
+        let root = automation.get_root_element()?;
+        let condition = automation.create_property_condition(UIA_ProcessIdPropertyId, Variant::from(self.get_id() as i32), None)?;
+        root.find_all(TreeScope_Children, &condition)
+    }
+
+    /// Get the full path of the executable image of the process.
+    pub fn get_image_path(&self) -> Result<String> {
+        get_image_path(self.information.hProcess)
+    }
+
     /// Get the exit code of the process.
     pub fn get_exit_code(&self) -> Result<u32> {
         let mut exit_code: u32 = 0;
@@ -119,17 +424,768 @@ impl Drop for Process {
     fn drop(&mut self) {
         unsafe {
             CloseHandle(self.information.hProcess);
-            CloseHandle(self.information.hThread);
+            // the process opened by id has no thread handle.
+            if !self.information.hThread.is_invalid() {
+                CloseHandle(self.information.hThread);
+            }
+        }
+    }
+}
+
+/// The exit status of a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExitStatus {
+    code: u32
+}
+
+impl ExitStatus {
+    /// Creates the exit status of the exit `code`.
+    pub fn new(code: u32) -> Self {
+        Self {
+            code
+        }
+    }
+
+    /// Get the exit code.
+    pub fn get_code(&self) -> u32 {
+        self.code
+    }
+
+    /// Determine whether the process exited successfully, which means the exit code is `0`.
+    pub fn is_success(&self) -> bool {
+        self.code == 0
+    }
+}
+
+impl Display for ExitStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // the exception codes such as `0xC0000005` are readable in hex.
+        if self.code >= 0xC0000000 {
+            write!(f, "exit code: {:#010X}", self.code)
+        } else {
+            write!(f, "exit code: {}", self.code)
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub use self::async_wait::ProcessWait;
+
+#[cfg(feature = "async")]
+mod async_wait {
+    use std::ffi::c_void;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+    use std::task::Context;
+    use std::task::Poll;
+    use std::task::Waker;
+
+    use windows::Win32::Foundation::BOOLEAN;
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::Foundation::INVALID_HANDLE_VALUE;
+    use windows::Win32::System::Threading::RegisterWaitForSingleObject;
+    use windows::Win32::System::Threading::UnregisterWaitEx;
+    use windows::Win32::System::Threading::WT_EXECUTEONLYONCE;
+
+    use super::Error;
+    use super::ExitStatus;
+    use super::Process;
+    use super::Result;
+
+    #[derive(Debug, Default)]
+    struct WaitState {
+        exited: AtomicBool,
+        waker: Mutex<Option<Waker>>
+    }
+
+    /// A future which resolves to the exit status when the process exits, created by `Process.wait_async()`.
+    #[derive(Debug)]
+    pub struct ProcessWait<'a> {
+        process: &'a Process,
+        state: Arc<WaitState>,
+        wait_handle: HANDLE
+    }
+
+    impl<'a> ProcessWait<'a> {
+        pub(super) fn new(process: &'a Process) -> Result<Self> {
+            let state = Arc::new(WaitState::default());
+            // the callback owns a reference, which is released after the wait is unregistered.
+            let context = Arc::into_raw(state.clone());
+
+            let mut wait_handle = HANDLE::default();
+            let ret = unsafe {
+                RegisterWaitForSingleObject(&mut wait_handle, process.information.hProcess, Some(on_exited), context as *const _, u32::MAX, WT_EXECUTEONLYONCE)
+            };
+
+            if ret.as_bool() {
+                Ok(Self {
+                    process,
+                    state,
+                    wait_handle
+                })
+            } else {
+                let err = Error::last_os_error();
+                unsafe { drop(Arc::from_raw(context)) };
+                Err(err)
+            }
+        }
+    }
+
+    impl<'a> Future for ProcessWait<'a> {
+        type Output = Result<ExitStatus>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            if !self.state.exited.load(Ordering::Acquire) {
+                if let Ok(mut waker) = self.state.waker.lock() {
+                    *waker = Some(cx.waker().clone());
+                }
+
+                // the process may exit before the waker is stored.
+                if !self.state.exited.load(Ordering::Acquire) {
+                    return Poll::Pending;
+                }
+            }
+
+            let status = self.process.get_exit_code().map(ExitStatus::new);
+            Poll::Ready(status)
+        }
+    }
+
+    impl<'a> Drop for ProcessWait<'a> {
+        fn drop(&mut self) {
+            unsafe {
+                // waits for the running callback, so the state can be released safely.
+                UnregisterWaitEx(self.wait_handle, INVALID_HANDLE_VALUE);
+                drop(Arc::from_raw(Arc::as_ptr(&self.state)));
+            }
+        }
+    }
+
+    unsafe extern "system" fn on_exited(context: *mut c_void, _timeout: BOOLEAN) {
+        let state = &*(context as *const WaitState);
+        state.exited.store(true, Ordering::Release);
+
+        let waker = state.waker.lock().ok().and_then(|mut waker| waker.take());
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// A Windows job object, which kills all its processes when it is closed.
+/// 
+/// The child processes created by the processes in the job are in the job too.
+#[derive(Debug)]
+pub struct Job {
+    handle: HANDLE
+}
+
+impl Job {
+    /// Create a job object with the kill-on-close limit.
+    pub fn new() -> Result<Self> {
+        let handle = unsafe { CreateJobObjectW(std::ptr::null(), PCWSTR::null())? };
+        let job = Self {
+            handle
+        };
+
+        let mut limit = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+        limit.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+        let ret = unsafe {
+            SetInformationJobObject(job.handle, JobObjectExtendedLimitInformation, &limit as *const _ as *const _, mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as _)
+        };
+
+        if ret.as_bool() {
+            Ok(job)
+        } else {
+            Err(Error::last_os_error())
+        }
+    }
+
+    /// Assign the `process` to the job.
+    pub fn assign(&self, process: &Process) -> Result<()> {
+        let ret = unsafe {
+            AssignProcessToJobObject(self.handle, process.information.hProcess)
+        };
+
+        if ret.as_bool() {
+            Ok(())
+        } else {
+            Err(Error::last_os_error())
+        }
+    }
+
+    /// Get the ids of the running processes in the job.
+    pub fn get_process_ids(&self) -> Result<Vec<u32>> {
+        let header = mem::size_of::<JOBOBJECT_BASIC_PROCESS_ID_LIST>() - mem::size_of::<usize>();
+        let mut capacity: usize = 64;
+        loop {
+            let size = header + capacity * mem::size_of::<usize>();
+            let mut buffer: Vec<usize> = vec![0; size / mem::size_of::<usize>()];
+            let ret = unsafe {
+                QueryInformationJobObject(self.handle, JobObjectBasicProcessIdList, buffer.as_mut_ptr() as *mut _, size as _, std::ptr::null_mut())
+            };
+
+            let list = unsafe { &*(buffer.as_ptr() as *const JOBOBJECT_BASIC_PROCESS_ID_LIST) };
+            if ret.as_bool() {
+                let ids = unsafe { std::slice::from_raw_parts(list.ProcessIdList.as_ptr(), list.NumberOfProcessIdsInList as usize) };
+                return Ok(ids.iter().map(|id| *id as u32).collect());
+            } else if list.NumberOfAssignedProcesses as usize > capacity {
+                capacity = list.NumberOfAssignedProcesses as usize + 16;
+            } else {
+                return Err(Error::last_os_error());
+            }
+        }
+    }
+
+    /// Exit all the processes in the job with `exit_code` by force.
+    pub fn terminate(&self, exit_code: u32) -> Result<()> {
+        let ret = unsafe {
+            TerminateJobObject(self.handle, exit_code)
+        };
+
+        if ret.as_bool() {
+            Ok(())
+        } else {
+            Err(Error::last_os_error())
+        }
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        unsafe {
+            CloseHandle(self.handle);
         }
     }
 }
 
+/// The information of a running process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessInfo {
+    id: u32,
+    parent_id: u32,
+    name: String,
+    image_path: Option<String>,
+    session_id: Option<u32>,
+    creation_time: Option<u64>
+}
+
+impl ProcessInfo {
+    fn new(entry: &PROCESSENTRY32W) -> Self {
+        let id = entry.th32ProcessID;
+        let (image_path, creation_time) = match unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, id) } {
+            Ok(handle) => {
+                let path = get_image_path(handle).ok();
+                let creation_time = get_creation_time(handle);
+                unsafe { CloseHandle(handle) };
+                (path, creation_time)
+            },
+            Err(_) => (None, None)
+        };
+        let mut session_id: u32 = 0;
+        let session_id = if unsafe { ProcessIdToSessionId(id, &mut session_id) }.as_bool() {
+            Some(session_id)
+        } else {
+            None
+        };
+
+        Self {
+            id,
+            parent_id: entry.th32ParentProcessID,
+            name: from_wide(&entry.szExeFile),
+            image_path,
+            session_id,
+            creation_time
+        }
+    }
+
+    /// Get the id of the process.
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+
+    /// Get the id of the parent process. The parent process may have exited and its id may be reused.
+    pub fn get_parent_id(&self) -> u32 {
+        self.parent_id
+    }
+
+    /// Get the image name of the process, such as `notepad.exe`.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the full path of the executable image, which is `None` if the process can not be queried, such as a protected system process.
+    pub fn get_image_path(&self) -> Option<&str> {
+        self.image_path.as_deref()
+    }
+
+    /// Get the terminal services session id of the process.
+    pub fn get_session_id(&self) -> Option<u32> {
+        self.session_id
+    }
+
+    /// Open the process.
+    pub fn open(&self) -> Result<Process> {
+        Process::from_pid(self.id)
+    }
+}
+
+fn get_image_path(handle: HANDLE) -> Result<String> {
+    let mut buffer = [0u16; 1024];
+    let mut size = buffer.len() as u32;
+    let ret = unsafe {
+        QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, PWSTR(buffer.as_mut_ptr()), &mut size)
+    };
+
+    if ret.as_bool() {
+        Ok(String::from_utf16_lossy(&buffer[..size as usize]))
+    } else {
+        Err(Error::last_os_error())
+    }
+}
+
+fn get_creation_time(handle: HANDLE) -> Option<u64> {
+    let mut creation_time = FILETIME::default();
+    let mut exit_time = FILETIME::default();
+    let mut kernel_time = FILETIME::default();
+    let mut user_time = FILETIME::default();
+    let ret = unsafe {
+        GetProcessTimes(handle, &mut creation_time, &mut exit_time, &mut kernel_time, &mut user_time)
+    };
+
+    if ret.as_bool() {
+        Some(((creation_time.dwHighDateTime as u64) << 32) | creation_time.dwLowDateTime as u64)
+    } else {
+        None
+    }
+}
+
+/// Collects the descendants of the process `id` from the `processes` snapshot, from parent to child.
+/// 
+/// A child is created after its parent, so the processes whose parent id is reused by another process are excluded.
+fn get_descendants(id: u32, creation_time: Option<u64>, processes: &[ProcessInfo]) -> Vec<&ProcessInfo> {
+    let mut descendants: Vec<&ProcessInfo> = Vec::new();
+    let mut parents: VecDeque<(u32, Option<u64>)> = VecDeque::from([(id, creation_time)]);
+    while let Some((parent_id, parent_time)) = parents.pop_front() {
+        for info in processes {
+            let created_after = match (info.creation_time, parent_time) {
+                (Some(child), Some(parent)) => child >= parent,
+                _ => true
+            };
+            if info.parent_id == parent_id && info.id != parent_id && info.id != id && created_after && !descendants.iter().any(|p| p.id == info.id) {
+                descendants.push(info);
+                parents.push_back((info.id, info.creation_time));
+            }
+        }
+    }
+
+    descendants
+}
+
+/// Converts the null-terminated wide string buffer.
+fn from_wide(buffer: &[u16]) -> String {
+    let len = buffer.iter().position(|ch| *ch == 0).unwrap_or(buffer.len());
+    String::from_utf16_lossy(&buffer[..len])
+}
+
+/// The standard input, output or error of a created process.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Stdio {
+    /// Inherits the standard handle of the current process.
+    #[default]
+    Inherit,
+    /// Redirects to the `NUL` device.
+    Null,
+    /// Redirects to the file. The standard input reads an existing file, and the standard output and error create or truncate the file.
+    File(String),
+    /// Redirects to the handle, which is duplicated for the process and is still owned by the caller.
+    Handle(HANDLE)
+}
+
+/// Builds and creates a process with arguments, working directory, environment, window state and standard handles.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use uiautomation::processes::ProcessBuilder;
+/// use windows::Win32::UI::WindowsAndMessaging::SW_SHOWMINIMIZED;
+/// 
+/// let notepad = ProcessBuilder::new("notepad.exe")
+///     .arg("C:\\Program Files\\readme.txt")
+///     .show_window(SW_SHOWMINIMIZED)
+///     .spawn()
+///     .unwrap();
+/// notepad.terminate(0).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProcessBuilder {
+    program: String,
+    args: Vec<String>,
+    current_dir: Option<String>,
+    env_clear: bool,
+    envs: Vec<(String, Option<String>)>,
+    show_window: Option<SHOW_WINDOW_CMD>,
+    creation_flags: PROCESS_CREATION_FLAGS,
+    kill_on_close: bool,
+    stdin: Stdio,
+    stdout: Stdio,
+    stderr: Stdio
+}
+
+impl ProcessBuilder {
+    /// Creates a builder which runs the `program`. The program is searched in the `PATH` if it is not a full path.
+    pub fn new(program: &str) -> Self {
+        Self {
+            program: String::from(program),
+            args: Vec::new(),
+            current_dir: None,
+            env_clear: false,
+            envs: Vec::new(),
+            show_window: None,
+            creation_flags: PROCESS_CREATION_FLAGS::default(),
+            kill_on_close: false,
+            stdin: Stdio::Inherit,
+            stdout: Stdio::Inherit,
+            stderr: Stdio::Inherit
+        }
+    }
+
+    /// Adds an argument, which is quoted by the Windows command line rules.
+    pub fn arg(mut self, arg: &str) -> Self {
+        self.args.push(String::from(arg));
+        self
+    }
+
+    /// Adds multiple arguments.
+    pub fn args(mut self, args: &[&str]) -> Self {
+        self.args.extend(args.iter().map(|arg| String::from(*arg)));
+        self
+    }
+
+    /// Sets the working directory of the process. The current directory of the caller is used by default.
+    pub fn current_dir(mut self, dir: &str) -> Self {
+        self.current_dir = Some(String::from(dir));
+        self
+    }
+
+    /// Sets the environment variable `key` to `value` for the process.
+    pub fn env(mut self, key: &str, value: &str) -> Self {
+        self.envs.push((String::from(key), Some(String::from(value))));
+        self
+    }
+
+    /// Removes the environment variable `key` for the process.
+    pub fn env_remove(mut self, key: &str) -> Self {
+        self.envs.push((String::from(key), None));
+        self
+    }
+
+    /// Clears the inherited environment variables, so the process only gets the variables set by `env()`.
+    pub fn env_clear(mut self) -> Self {
+        self.env_clear = true;
+        self.envs.clear();
+        self
+    }
+
+    /// Sets the initial show state of the main window, such as `SW_HIDE`, `SW_SHOWMINIMIZED` or `SW_SHOWMAXIMIZED`.
+    pub fn show_window(mut self, show_window: SHOW_WINDOW_CMD) -> Self {
+        self.show_window = Some(show_window);
+        self
+    }
+
+    /// Sets the process creation flags, such as `CREATE_NEW_CONSOLE` or `CREATE_NO_WINDOW`.
+    pub fn creation_flags(mut self, creation_flags: PROCESS_CREATION_FLAGS) -> Self {
+        self.creation_flags = creation_flags;
+        self
+    }
+
+    /// Sets whether to create the process in a new job object, which kills the process and all its descendants
+    /// when the `Process` is dropped. Default is `false`.
+    pub fn kill_on_close(mut self, kill_on_close: bool) -> Self {
+        self.kill_on_close = kill_on_close;
+        self
+    }
+
+    /// Sets the standard input of the process.
+    pub fn stdin(mut self, stdin: Stdio) -> Self {
+        self.stdin = stdin;
+        self
+    }
+
+    /// Sets the standard output of the process.
+    pub fn stdout(mut self, stdout: Stdio) -> Self {
+        self.stdout = stdout;
+        self
+    }
+
+    /// Sets the standard error of the process.
+    pub fn stderr(mut self, stderr: Stdio) -> Self {
+        self.stderr = stderr;
+        self
+    }
+
+    /// Retrieves the command line which is passed to the process.
+    pub fn get_command_line(&self) -> String {
+        create_command_line(&self.program, &self.args)
+    }
+
+    /// Creates the process.
+    pub fn spawn(&self) -> Result<Process> {
+        let mut command = to_wide(&self.get_command_line());
+        let current_dir = self.current_dir.as_deref().map(to_wide);
+
+        let mut creation_flags = self.creation_flags;
+        let job = if self.kill_on_close {
+            // the process is resumed after it is assigned to the job, so its children are in the job too.
+            creation_flags |= CREATE_SUSPENDED;
+            Some(Job::new()?)
+        } else {
+            None
+        };
+        let environment = if self.env_clear || !self.envs.is_empty() {
+            creation_flags |= CREATE_UNICODE_ENVIRONMENT;
+            let base = if self.env_clear {
+                Vec::new()
+            } else {
+                std::env::vars_os().map(|(k, v)| (k.to_string_lossy().into_owned(), v.to_string_lossy().into_owned())).collect()
+            };
+            Some(create_environment_block(&merge_environment(base, &self.envs)))
+        } else {
+            None
+        };
+
+        let mut si = Process::startupinfo();
+        if let Some(show_window) = self.show_window {
+            si.dwFlags |= STARTF_USESHOWWINDOW;
+            si.wShowWindow = show_window.0 as _;
+        }
+
+        let redirected = self.stdin != Stdio::Inherit || self.stdout != Stdio::Inherit || self.stderr != Stdio::Inherit;
+        let handles = if redirected {
+            let handles = [
+                open_stdio(&self.stdin, STD_INPUT_HANDLE)?,
+                open_stdio(&self.stdout, STD_OUTPUT_HANDLE)?,
+                open_stdio(&self.stderr, STD_ERROR_HANDLE)?
+            ];
+            si.dwFlags |= STARTF_USESTDHANDLES;
+            si.hStdInput = handles[0].0;
+            si.hStdOutput = handles[1].0;
+            si.hStdError = handles[2].0;
+            Some(handles)
+        } else {
+            None
+        };
+
+        let mut information = PROCESS_INFORMATION::default();
+        let ret = unsafe {
+            CreateProcessW(PCWSTR::null(),
+                PWSTR(command.as_mut_ptr()),
+                std::ptr::null(),
+                std::ptr::null(),
+                handles.is_some(),
+                creation_flags,
+                environment.as_ref().map_or(std::ptr::null(), |env| env.as_ptr() as *const _),
+                current_dir.as_ref().map_or(PCWSTR::null(), |dir| PCWSTR(dir.as_ptr())),
+                &si,
+                &mut information)
+        };
+
+        if !ret.as_bool() {
+            return Err(Error::last_os_error());
+        }
+
+        let mut process = Process {
+            information,
+            job: None
+        };
+        if let Some(job) = job {
+            if let Err(e) = job.assign(&process) {
+                let _ = process.terminate(1);
+                return Err(e);
+            }
+            process.job = Some(job);
+
+            if self.creation_flags.0 & CREATE_SUSPENDED.0 == 0 {
+                unsafe { ResumeThread(process.information.hThread) };
+            }
+        }
+
+        Ok(process)
+    }
+}
+
+/// An inheritable handle which is passed to the created process, and closed after the creation.
+struct StdioHandle(HANDLE);
+
+impl Drop for StdioHandle {
+    fn drop(&mut self) {
+        if !self.0.is_invalid() {
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+}
+
+fn open_stdio(stdio: &Stdio, std_handle: STD_HANDLE) -> Result<StdioHandle> {
+    let handle = match stdio {
+        Stdio::Inherit => {
+            let handle = unsafe { GetStdHandle(std_handle) }.unwrap_or_default();
+            return duplicate_handle(handle);
+        },
+        Stdio::Handle(handle) => return duplicate_handle(*handle),
+        Stdio::Null => open_file("NUL", std_handle)?,
+        Stdio::File(path) => open_file(path, std_handle)?
+    };
+
+    Ok(StdioHandle(handle))
+}
+
+fn open_file(path: &str, std_handle: STD_HANDLE) -> Result<HANDLE> {
+    let path = to_wide(path);
+    let sa = SECURITY_ATTRIBUTES {
+        nLength: mem::size_of::<SECURITY_ATTRIBUTES>() as _,
+        lpSecurityDescriptor: std::ptr::null_mut(),
+        bInheritHandle: BOOL::from(true)
+    };
+    let (access, disposition) = if std_handle == STD_INPUT_HANDLE {
+        (FILE_GENERIC_READ, OPEN_EXISTING)
+    } else {
+        (FILE_GENERIC_WRITE, CREATE_ALWAYS)
+    };
+
+    let handle = unsafe {
+        CreateFileW(PCWSTR(path.as_ptr()), access, FILE_SHARE_READ | FILE_SHARE_WRITE, &sa, disposition, FILE_ATTRIBUTE_NORMAL, HANDLE::default())?
+    };
+    Ok(handle)
+}
+
+fn duplicate_handle(handle: HANDLE) -> Result<StdioHandle> {
+    if handle.is_invalid() || handle.0 == 0 {
+        return Ok(StdioHandle(HANDLE::default()));
+    }
+
+    let mut target = HANDLE::default();
+    let ret = unsafe {
+        DuplicateHandle(GetCurrentProcess(), handle, GetCurrentProcess(), &mut target, 0, true, DUPLICATE_SAME_ACCESS)
+    };
+
+    if ret.as_bool() {
+        Ok(StdioHandle(target))
+    } else {
+        Err(Error::last_os_error())
+    }
+}
+
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Quotes the argument by the rules of `CommandLineToArgvW()` and the C runtime, so the process receives it unchanged.
+/// 
+/// The argument is quoted when it is empty or has whitespaces or quotes. The backslashes are only escaped before a quote.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use uiautomation::processes::quote_arg;
+/// 
+/// assert_eq!(quote_arg("simple"), "simple");
+/// assert_eq!(quote_arg("C:\\Program Files\\"), "\"C:\\Program Files\\\\\"");
+/// assert_eq!(quote_arg("say \"hi\""), "\"say \\\"hi\\\"\"");
+/// ```
+pub fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '\n', '\x0B', '"']) {
+        return String::from(arg);
+    }
+
+    let mut quoted = String::from("\"");
+    let mut backslashes: usize = 0;
+    for ch in arg.chars() {
+        match ch {
+            '\\' => backslashes += 1,
+            '"' => {
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            },
+            _ => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                quoted.push(ch);
+                backslashes = 0;
+            }
+        }
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+
+    quoted
+}
+
+/// Creates the command line of the `program` and the `args`.
+/// 
+/// The program name is parsed without backslash escapes, so it is only enclosed in quotes when it has whitespaces.
+pub fn create_command_line(program: &str, args: &[String]) -> String {
+    let mut command = if program.is_empty() || program.contains([' ', '\t']) {
+        format!("\"{}\"", program)
+    } else {
+        String::from(program)
+    };
+
+    for arg in args {
+        command.push(' ');
+        command.push_str(&quote_arg(arg));
+    }
+
+    command
+}
+
+/// Applies the environment overrides to the `base` variables. The names are case-insensitive, and the result is sorted by name.
+fn merge_environment(base: Vec<(String, String)>, envs: &[(String, Option<String>)]) -> Vec<(String, String)> {
+    let mut vars = base;
+    for (key, value) in envs {
+        vars.retain(|(k, _)| !k.eq_ignore_ascii_case(key));
+        if let Some(value) = value {
+            vars.push((key.clone(), value.clone()));
+        }
+    }
+
+    vars.sort_by_key(|(k, _)| k.to_uppercase());
+    vars
+}
+
+/// Creates the unicode environment block, which is a sequence of `name=value\0` terminated by another `\0`.
+fn create_environment_block(vars: &[(String, String)]) -> Vec<u16> {
+    let mut block: Vec<u16> = Vec::new();
+    for (key, value) in vars {
+        block.extend(format!("{}={}", key, value).encode_utf16());
+        block.push(0);
+    }
+    if block.is_empty() {
+        block.push(0);
+    }
+    block.push(0);
+
+    block
+}
+
 #[cfg(test)]
 mod tests {
     use std::thread::sleep;
     use std::time::Duration;
 
     use crate::processes::Process;
+    use crate::processes::create_command_line;
+    use crate::processes::create_environment_block;
+    use crate::processes::ExitStatus;
+    use crate::processes::ProcessBuilder;
+    use crate::processes::ProcessInfo;
+    use crate::processes::from_wide;
+    use crate::processes::get_descendants;
+    use crate::processes::merge_environment;
+    use crate::processes::quote_arg;
 
     #[test]
     fn run_notepad() {
@@ -145,4 +1201,148 @@ mod tests {
         let exit_code = notepad.get_exit_code().unwrap();
         assert_eq!(exit_code, 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_quote_arg() {
+        assert_eq!(quote_arg("abc"), "abc");
+        assert_eq!(quote_arg(""), "\"\"");
+        assert_eq!(quote_arg("a b"), "\"a b\"");
+        assert_eq!(quote_arg("a\\b"), "a\\b");
+        assert_eq!(quote_arg("a\\\\b c"), "\"a\\\\b c\"");
+        assert_eq!(quote_arg("a\"b"), "\"a\\\"b\"");
+        assert_eq!(quote_arg("a\\\"b"), "\"a\\\\\\\"b\"");
+        assert_eq!(quote_arg("dir\\ "), "\"dir\\ \"");
+        assert_eq!(quote_arg("dir name\\"), "\"dir name\\\\\"");
+        assert_eq!(quote_arg("中文 参数"), "\"中文 参数\"");
+    }
+
+    #[test]
+    fn test_command_line() {
+        let args = vec![String::from("-n"), String::from("hello world"), String::new()];
+        assert_eq!(create_command_line("notepad.exe", &args), "notepad.exe -n \"hello world\" \"\"");
+        assert_eq!(create_command_line("C:\\Program Files\\app.exe", &[]), "\"C:\\Program Files\\app.exe\"");
+    }
+
+    #[test]
+    fn test_environment() {
+        let base = vec![(String::from("Path"), String::from("C:\\")), (String::from("TEMP"), String::from("D:\\"))];
+        let envs = vec![(String::from("PATH"), Some(String::from("E:\\"))), (String::from("temp"), None), (String::from("App"), Some(String::from("1")))];
+        let vars = merge_environment(base, &envs);
+        assert_eq!(vars, vec![(String::from("App"), String::from("1")), (String::from("PATH"), String::from("E:\\"))]);
+
+        let block = create_environment_block(&vars);
+        assert_eq!(String::from_utf16(&block).unwrap(), "App=1\0PATH=E:\\\0\0");
+        assert_eq!(create_environment_block(&[]), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_from_wide() {
+        let mut buffer = [0u16; 16];
+        buffer[..7].copy_from_slice(&"app.exe".encode_utf16().collect::<Vec<u16>>());
+        assert_eq!(from_wide(&buffer), "app.exe");
+        assert_eq!(from_wide(&[0x61, 0x62]), "ab");
+    }
+
+    #[test]
+    fn find_current_process() {
+        let id = std::process::id();
+        let processes = Process::enumerate().unwrap();
+        let current = processes.iter().find(|p| p.get_id() == id).unwrap();
+        assert!(current.get_image_path().is_some());
+
+        let process = current.open().unwrap();
+        assert_eq!(process.get_image_path().unwrap(), current.get_image_path().unwrap());
+        assert!(Process::find_by_name(current.get_name()).unwrap().iter().any(|p| p.get_id() == id));
+    }
+
+    fn create_info(id: u32, parent_id: u32, creation_time: u64) -> ProcessInfo {
+        ProcessInfo {
+            id,
+            parent_id,
+            name: format!("{}.exe", id),
+            image_path: None,
+            session_id: Some(1),
+            creation_time: Some(creation_time)
+        }
+    }
+
+    #[test]
+    fn test_descendants() {
+        let processes = vec![
+            create_info(0, 0, 0),
+            create_info(10, 1, 100),
+            create_info(11, 10, 110),
+            create_info(12, 10, 120),
+            create_info(13, 11, 130),
+            create_info(14, 10, 50),
+            create_info(15, 14, 60),
+            create_info(20, 2, 200)
+        ];
+
+        let ids: Vec<u32> = get_descendants(10, Some(100), &processes).iter().map(|p| p.get_id()).collect();
+        assert_eq!(ids, vec![11, 12, 13]);
+        assert!(get_descendants(20, Some(200), &processes).is_empty());
+        assert!(get_descendants(0, Some(0), &processes).is_empty());
+    }
+
+    #[test]
+    fn terminate_tree() {
+        let cmd = ProcessBuilder::new("cmd.exe").args(&["/c", "ping", "-n", "30", "127.0.0.1"]).kill_on_close(true).spawn().unwrap();
+        sleep(Duration::from_secs(1));
+
+        let killed = cmd.terminate_tree(1).unwrap();
+        assert_eq!(killed[0].get_id(), cmd.get_id());
+        assert!(killed.iter().any(|p| p.get_name().eq_ignore_ascii_case("ping.exe")));
+        cmd.wait_for(1000).unwrap();
+    }
+
+    #[test]
+    fn test_exit_status() {
+        assert!(ExitStatus::new(0).is_success());
+        assert!(!ExitStatus::new(1).is_success());
+        assert_eq!(ExitStatus::new(3).to_string(), "exit code: 3");
+        assert_eq!(ExitStatus::new(0xC0000005).to_string(), "exit code: 0xC0000005");
+    }
+
+    #[test]
+    fn wait_timeout() {
+        let cmd = ProcessBuilder::new("cmd.exe").args(&["/c", "ping", "-n", "2", "127.0.0.1"]).spawn().unwrap();
+        assert!(cmd.is_running().unwrap());
+        assert_eq!(cmd.wait_timeout(Duration::ZERO).unwrap(), None);
+
+        let status = cmd.wait_timeout(Duration::from_secs(10)).unwrap().unwrap();
+        assert!(status.is_success());
+        assert!(!cmd.is_running().unwrap());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn wait_async() {
+        use std::future::Future;
+        use std::sync::Arc;
+        use std::task::Context;
+        use std::task::Poll;
+        use std::task::Wake;
+        use std::thread::Thread;
+
+        struct ThreadWaker(Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let cmd = ProcessBuilder::new("cmd.exe").args(&["/c", "exit", "3"]).spawn().unwrap();
+        let mut wait = Box::pin(cmd.wait_async().unwrap());
+        let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        let status = loop {
+            match wait.as_mut().poll(&mut cx) {
+                Poll::Ready(status) => break status.unwrap(),
+                Poll::Pending => std::thread::park()
+            }
+        };
+        assert_eq!(status.get_code(), 3);
+    }
+}
@@ -0,0 +1,303 @@
+use windows::Win32::Foundation::BOOL;
+use windows::Win32::Foundation::LPARAM;
+use windows::Win32::Foundation::RECT;
+use windows::Win32::Graphics::Gdi::EnumDisplayMonitors;
+use windows::Win32::Graphics::Gdi::GetMonitorInfoW;
+use windows::Win32::Graphics::Gdi::HDC;
+use windows::Win32::Graphics::Gdi::HMONITOR;
+use windows::Win32::Graphics::Gdi::MONITORINFO;
+use windows::Win32::Graphics::Gdi::MONITORINFOEXW;
+use windows::Win32::Graphics::Gdi::MONITOR_DEFAULTTONEAREST;
+use windows::Win32::Graphics::Gdi::MonitorFromPoint;
+use windows::Win32::UI::HiDpi::DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2;
+use windows::Win32::UI::HiDpi::GetDpiForMonitor;
+use windows::Win32::UI::HiDpi::GetDpiForWindow;
+use windows::Win32::UI::HiDpi::MDT_EFFECTIVE_DPI;
+use windows::Win32::UI::HiDpi::SetProcessDpiAwarenessContext;
+use windows::Win32::UI::WindowsAndMessaging::GetSystemMetrics;
+use windows::Win32::UI::WindowsAndMessaging::MONITORINFOF_PRIMARY;
+use windows::Win32::UI::WindowsAndMessaging::SM_CXVIRTUALSCREEN;
+use windows::Win32::UI::WindowsAndMessaging::SM_CYVIRTUALSCREEN;
+use windows::Win32::UI::WindowsAndMessaging::SM_XVIRTUALSCREEN;
+use windows::Win32::UI::WindowsAndMessaging::SM_YVIRTUALSCREEN;
+
+use super::Error;
+use super::errors::ERR_NOTFOUND;
+use super::Result;
+use super::types::Handle;
+use super::types::Point;
+use super::types::Rect;
+
+/// The default DPI of a monitor, which is 100% scaling.
+pub const DEFAULT_DPI: u32 = 96;
+
+/// A display monitor of the virtual desktop.
+#[derive(Debug, Clone)]
+pub struct Monitor {
+    handle: HMONITOR,
+    name: String,
+    bounds: Rect,
+    work_area: Rect,
+    primary: bool,
+    dpi: u32
+}
+
+impl Monitor {
+    /// Retrieves all the display monitors.
+    pub fn get_monitors() -> Result<Vec<Monitor>> {
+        let mut handles: Vec<HMONITOR> = Vec::new();
+        let ret = unsafe {
+            EnumDisplayMonitors(HDC::default(), std::ptr::null(), Some(enum_monitor_proc), LPARAM(&mut handles as *mut Vec<HMONITOR> as _))
+        };
+        if !ret.as_bool() {
+            return Err(Error::last_os_error());
+        }
+
+        handles.into_iter().map(Self::from_handle).collect()
+    }
+
+    /// Retrieves the monitor which contains the `point`, or the nearest monitor if no monitor contains it.
+    pub fn from_point(point: Point) -> Result<Monitor> {
+        let handle = unsafe { MonitorFromPoint(point.into(), MONITOR_DEFAULTTONEAREST) };
+        Self::from_handle(handle)
+    }
+
+    /// Retrieves the monitor of the `HMONITOR` handle.
+    pub fn from_handle(handle: HMONITOR) -> Result<Monitor> {
+        let mut info = MONITORINFOEXW::default();
+        info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as _;
+        let ret = unsafe { GetMonitorInfoW(handle, &mut info as *mut MONITORINFOEXW as *mut MONITORINFO) };
+        if !ret.as_bool() {
+            return Err(Error::last_os_error());
+        }
+
+        let mut dpi_x: u32 = DEFAULT_DPI;
+        let mut dpi_y: u32 = DEFAULT_DPI;
+        if unsafe { GetDpiForMonitor(handle, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) }.is_err() {
+            dpi_x = DEFAULT_DPI;
+        }
+
+        let len = info.szDevice.iter().position(|c| *c == 0).unwrap_or(info.szDevice.len());
+        Ok(Monitor {
+            handle,
+            name: String::from_utf16_lossy(&info.szDevice[..len]),
+            bounds: info.monitorInfo.rcMonitor.into(),
+            work_area: info.monitorInfo.rcWork.into(),
+            primary: (info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY) != 0,
+            dpi: dpi_x
+        })
+    }
+
+    /// Retrieves the `HMONITOR` handle of the monitor.
+    pub fn get_handle(&self) -> HMONITOR {
+        self.handle
+    }
+
+    /// Retrieves the device name of the monitor, such as `\\.\DISPLAY1`.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Retrieves the bounds of the monitor, in virtual-desktop coordinates.
+    pub fn get_bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    /// Retrieves the work area of the monitor, which excludes the taskbar and docked toolbars.
+    pub fn get_work_area(&self) -> Rect {
+        self.work_area
+    }
+
+    /// Indicates whether the monitor is the primary monitor.
+    pub fn is_primary(&self) -> bool {
+        self.primary
+    }
+
+    /// Retrieves the effective DPI of the monitor. `96` is 100% scaling.
+    pub fn get_dpi(&self) -> u32 {
+        self.dpi
+    }
+
+    /// Retrieves the scale factor of the monitor, such as `1.5` for 150% scaling.
+    pub fn get_scale_factor(&self) -> f64 {
+        self.dpi as f64 / DEFAULT_DPI as f64
+    }
+
+    /// Converts the physical `point` on this monitor to logical pixels.
+    pub fn to_logical(&self, point: Point) -> Point {
+        physical_to_logical(point, self.bounds, self.get_scale_factor())
+    }
+
+    /// Converts the logical `point` on this monitor to physical pixels.
+    pub fn to_physical(&self, point: Point) -> Point {
+        logical_to_physical(point, self.bounds, self.get_scale_factor())
+    }
+}
+
+unsafe extern "system" fn enum_monitor_proc(handle: HMONITOR, _hdc: HDC, _rect: *mut RECT, data: LPARAM) -> BOOL {
+    let handles = &mut *(data.0 as *mut Vec<HMONITOR>);
+    handles.push(handle);
+    true.into()
+}
+
+/// Makes the current process per-monitor DPI aware, so all the coordinates are physical pixels.
+/// 
+/// The DPI awareness can only be set once for a process. An error is returned if it has been set, 
+/// for example by the application manifest.
+pub fn set_dpi_awareness() -> Result<()> {
+    let ret = unsafe { SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2) };
+    if ret.as_bool() {
+        Ok(())
+    } else {
+        Err(Error::last_os_error())
+    }
+}
+
+/// Retrieves the scale factor of the monitor which contains the `point`.
+pub fn get_scale_factor(point: Point) -> Result<f64> {
+    let monitor = Monitor::from_point(point)?;
+    Ok(monitor.get_scale_factor())
+}
+
+/// Retrieves the scale factor of the window of the `handle`.
+pub fn get_window_scale_factor(handle: Handle) -> Result<f64> {
+    let dpi = unsafe { GetDpiForWindow(handle) };
+    if dpi == 0 {
+        Err(Error::new(ERR_NOTFOUND, "Invalid window handle"))
+    } else {
+        Ok(dpi as f64 / DEFAULT_DPI as f64)
+    }
+}
+
+/// Converts the physical `point` on a monitor of the physical `bounds` to logical pixels of the `scale` factor.
+/// 
+/// The offset from the top left of the monitor is scaled, and the result is kept inside the logical bounds of the monitor,
+/// so a point on the last pixel never rounds onto the next monitor.
+pub fn physical_to_logical(point: Point, bounds: Rect, scale: f64) -> Point {
+    let origin = bounds.get_top_left();
+    let offset = Point::new(point.get_x() - origin.get_x(), point.get_y() - origin.get_y()).to_logical(scale);
+    let size = Point::new(bounds.get_width(), bounds.get_height()).to_logical(scale);
+    Point::new(
+        origin.get_x() + clamp_offset(offset.get_x(), size.get_x()),
+        origin.get_y() + clamp_offset(offset.get_y(), size.get_y())
+    )
+}
+
+/// Converts the logical `point` on a monitor of the physical `bounds` to physical pixels of the `scale` factor.
+/// 
+/// The result is kept inside the physical `bounds` of the monitor.
+pub fn logical_to_physical(point: Point, bounds: Rect, scale: f64) -> Point {
+    let origin = bounds.get_top_left();
+    let offset = Point::new(point.get_x() - origin.get_x(), point.get_y() - origin.get_y()).to_physical(scale);
+    Point::new(
+        origin.get_x() + clamp_offset(offset.get_x(), bounds.get_width()),
+        origin.get_y() + clamp_offset(offset.get_y(), bounds.get_height())
+    )
+}
+
+fn clamp_offset(offset: i32, size: i32) -> i32 {
+    if size > 0 && (0..=size).contains(&offset) {
+        offset.min(size - 1)
+    } else {
+        offset
+    }
+}
+
+/// Retrieves the bounds of the virtual screen, which covers all the monitors.
+/// 
+/// The left and top can be negative when a monitor is placed at the left or top of the primary monitor.
+pub fn get_virtual_screen() -> Result<Rect> {
+    let (left, top, width, height) = unsafe {(
+        GetSystemMetrics(SM_XVIRTUALSCREEN),
+        GetSystemMetrics(SM_YVIRTUALSCREEN),
+        GetSystemMetrics(SM_CXVIRTUALSCREEN),
+        GetSystemMetrics(SM_CYVIRTUALSCREEN)
+    )};
+
+    if width == 0 || height == 0 {
+        Err(Error::last_os_error())
+    } else {
+        Ok(Rect::new(left, top, left + width, top + height))
+    }
+}
+
+/// Clamps the `point` into the monitor which contains it, or into the nearest monitor of the `monitors` bounds.
+/// 
+/// The `point` is returned unchanged when `monitors` is empty.
+pub fn clamp_to_monitors(point: Point, monitors: &[Rect]) -> Point {
+    if monitors.iter().any(|m| m.contains(point)) {
+        return point;
+    }
+
+    monitors.iter().filter(|m| m.get_width() > 0 && m.get_height() > 0).map(|m| {
+        Point::new(
+            point.get_x().clamp(m.get_left(), m.get_right() - 1),
+            point.get_y().clamp(m.get_top(), m.get_bottom() - 1)
+        )
+    }).min_by_key(|p| {
+        let dx = (p.get_x() - point.get_x()) as i64;
+        let dy = (p.get_y() - point.get_y()) as i64;
+        dx * dx + dy * dy
+    }).unwrap_or(point)
+}
+
+/// Converts the `point` to the normalized absolute coordinates of the `virtual_screen`, which are in range `0..=65535`.
+/// 
+/// The result is used by absolute mouse events with `MOUSEEVENTF_VIRTUALDESK`.
+pub fn to_absolute_coordinates(point: Point, virtual_screen: Rect) -> (i32, i32) {
+    fn normalize(value: i32, start: i32, size: i32) -> i32 {
+        if size <= 1 {
+            0
+        } else {
+            let offset = (value - start).clamp(0, size - 1) as i64;
+            ((offset * 65535 + (size as i64 - 1) / 2) / (size as i64 - 1)) as i32
+        }
+    }
+
+    (
+        normalize(point.get_x(), virtual_screen.get_left(), virtual_screen.get_width()),
+        normalize(point.get_y(), virtual_screen.get_top(), virtual_screen.get_height())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::screens::clamp_to_monitors;
+    use crate::screens::logical_to_physical;
+    use crate::screens::physical_to_logical;
+    use crate::screens::to_absolute_coordinates;
+    use crate::types::Point;
+    use crate::types::Rect;
+
+    #[test]
+    fn test_clamp_to_monitors() {
+        let monitors = [Rect::new(0, 0, 1920, 1080), Rect::new(-1280, 0, 0, 1024)];
+        assert_eq!(clamp_to_monitors(Point::new(-100, 500), &monitors), Point::new(-100, 500));
+        assert_eq!(clamp_to_monitors(Point::new(2000, 500), &monitors), Point::new(1919, 500));
+        assert_eq!(clamp_to_monitors(Point::new(-100, 1050), &monitors), Point::new(-100, 1023));
+        assert_eq!(clamp_to_monitors(Point::new(-2000, -10), &monitors), Point::new(-1280, 0));
+        assert_eq!(clamp_to_monitors(Point::new(5, 5), &[]), Point::new(5, 5));
+    }
+
+    #[test]
+    fn test_absolute_coordinates() {
+        let screen = Rect::new(-1280, 0, 1920, 1080);
+        assert_eq!(to_absolute_coordinates(Point::new(-1280, 0), screen), (0, 0));
+        assert_eq!(to_absolute_coordinates(Point::new(1919, 1079), screen), (65535, 65535));
+        assert_eq!(to_absolute_coordinates(Point::new(0, 540), screen), (26222, 32798));
+        assert_eq!(to_absolute_coordinates(Point::new(5000, -5), screen), (65535, 0));
+    }
+
+    #[test]
+    fn test_monitor_scale() {
+        let bounds = Rect::new(1920, 0, 3840, 1080);
+        assert_eq!(physical_to_logical(Point::new(1920, 0), bounds, 1.5), Point::new(1920, 0));
+        assert_eq!(physical_to_logical(Point::new(2220, 150), bounds, 1.5), Point::new(2120, 100));
+        assert_eq!(logical_to_physical(Point::new(2120, 100), bounds, 1.5), Point::new(2220, 150));
+
+        // 1920 / 1.75 = 1097.14, the last pixel 1919 / 1.75 = 1096.57 rounds to the logical width.
+        assert_eq!(physical_to_logical(Point::new(3839, 1079), bounds, 1.75), Point::new(1920 + 1096, 616));
+        assert_eq!(logical_to_physical(Point::new(1920 + 1097, 617), bounds, 1.75), Point::new(3839, 1079));
+        assert_eq!(physical_to_logical(Point::new(1919, 0), bounds, 1.75), Point::new(1919, 0));
+    }
+}
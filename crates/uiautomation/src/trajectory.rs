@@ -0,0 +1,265 @@
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use rand::Rng;
+use rand::RngCore;
+use rand::SeedableRng;
+use rand::rngs::SmallRng;
+
+use super::types::Point;
+
+/// The profile of a mouse trajectory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrajectoryProfile {
+    /// Moves along a straight line at constant speed.
+    Linear,
+    /// Moves along a straight line, accelerating at the start and decelerating at the end.
+    EaseInOut,
+    /// Moves along a cubic Bézier curve with random control points.
+    Bezier,
+    /// Moves a little past the target and then corrects back to it.
+    Overshoot
+}
+
+/// A point of a trajectory and the time to reach it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimedPoint {
+    point: Point,
+    time: u64
+}
+
+impl TimedPoint {
+    /// Creates a timed point.
+    pub fn new(point: Point, time: u64) -> Self {
+        Self {
+            point,
+            time
+        }
+    }
+
+    /// Retrieves the position of the point.
+    pub fn get_point(&self) -> Point {
+        self.point
+    }
+
+    /// Retrieves the time in milliseconds from the start of the trajectory.
+    pub fn get_time(&self) -> u64 {
+        self.time
+    }
+}
+
+/// A generator of mouse trajectories.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use rand::SeedableRng;
+/// use rand::rngs::SmallRng;
+/// use uiautomation::trajectory::Trajectory;
+/// use uiautomation::trajectory::TrajectoryProfile;
+/// use uiautomation::types::Point;
+/// 
+/// let trajectory = Trajectory::new(TrajectoryProfile::Bezier).jitter(1);
+/// let mut rng = SmallRng::seed_from_u64(42);
+/// let points = trajectory.generate(Point::new(0, 0), Point::new(400, 300), 500, &mut rng);
+/// assert_eq!(points.last().unwrap().get_point(), Point::new(400, 300));
+/// assert_eq!(points.last().unwrap().get_time(), 500);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trajectory {
+    profile: TrajectoryProfile,
+    step: i32,
+    jitter: i32
+}
+
+impl Default for Trajectory {
+    fn default() -> Self {
+        Self::new(TrajectoryProfile::EaseInOut)
+    }
+}
+
+impl Trajectory {
+    /// Creates a trajectory generator of the `profile`.
+    pub fn new(profile: TrajectoryProfile) -> Self {
+        Self {
+            profile,
+            step: 20,
+            jitter: 0
+        }
+    }
+
+    /// Sets the average distance in pixels between two points. `20` is default value.
+    pub fn step(mut self, step: i32) -> Self {
+        self.step = step.max(1);
+        self
+    }
+
+    /// Sets the maximum random offset in pixels applied to the intermediate points. `0` is default value.
+    pub fn jitter(mut self, jitter: i32) -> Self {
+        self.jitter = jitter.max(0);
+        self
+    }
+
+    /// Retrieves the profile of the trajectory.
+    pub fn get_profile(&self) -> TrajectoryProfile {
+        self.profile
+    }
+
+    /// Generates the points from `from` to `to` which take `duration` milliseconds.
+    /// 
+    /// The start point is not included. The last point is always `to` at `duration`.
+    /// The same `rng` state always generates the same points.
+    pub fn generate<R: RngCore + ?Sized>(&self, from: Point, to: Point, duration: u64, rng: &mut R) -> Vec<TimedPoint> {
+        let start = (from.get_x() as f64, from.get_y() as f64);
+        let end = (to.get_x() as f64, to.get_y() as f64);
+        let distance = (end.0 - start.0).hypot(end.1 - start.1);
+        let steps = ((distance / self.step as f64).ceil() as usize).max(1);
+
+        let path: Vec<(f64, f64)> = match self.profile {
+            TrajectoryProfile::Linear => (1..=steps).map(|i| lerp(start, end, i as f64 / steps as f64)).collect(),
+            TrajectoryProfile::EaseInOut => (1..=steps).map(|i| lerp(start, end, ease_in_out(i as f64 / steps as f64))).collect(),
+            TrajectoryProfile::Bezier => {
+                let c1 = control_point(start, end, 0.3, distance, rng);
+                let c2 = control_point(start, end, 0.7, distance, rng);
+                (1..=steps).map(|i| bezier(start, c1, c2, end, ease_in_out(i as f64 / steps as f64))).collect()
+            },
+            TrajectoryProfile::Overshoot => {
+                let over = overshoot_point(start, end, distance, rng);
+                let main_steps = ((steps * 4) / 5).max(1);
+                let back_steps = (steps - main_steps).max(1);
+                let mut path: Vec<(f64, f64)> = (1..=main_steps).map(|i| lerp(start, over, ease_in_out(i as f64 / main_steps as f64))).collect();
+                path.extend((1..=back_steps).map(|i| lerp(over, end, ease_in_out(i as f64 / back_steps as f64))));
+                path
+            }
+        };
+
+        let count = path.len();
+        path.into_iter().enumerate().map(|(i, (x, y))| {
+            let time = duration * (i + 1) as u64 / count as u64;
+            if i + 1 == count {
+                TimedPoint::new(to, time)
+            } else {
+                let (dx, dy) = if self.jitter > 0 {
+                    (rng.gen_range(-self.jitter..=self.jitter), rng.gen_range(-self.jitter..=self.jitter))
+                } else {
+                    (0, 0)
+                };
+                TimedPoint::new(Point::new(x.round() as i32 + dx, y.round() as i32 + dy), time)
+            }
+        }).collect()
+    }
+}
+
+/// Creates a small random number generator which is seeded by `seed`, or by the current time if `seed` is `None`.
+pub fn create_rng(seed: Option<u64>) -> SmallRng {
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or_default()
+    });
+    SmallRng::seed_from_u64(seed)
+}
+
+fn lerp(from: (f64, f64), to: (f64, f64), t: f64) -> (f64, f64) {
+    (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t)
+}
+
+fn ease_in_out(t: f64) -> f64 {
+    if t < 0.5 {
+        2.0 * t * t
+    } else {
+        1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+    }
+}
+
+fn bezier(p0: (f64, f64), p1: (f64, f64), p2: (f64, f64), p3: (f64, f64), t: f64) -> (f64, f64) {
+    let u = 1.0 - t;
+    let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+    (
+        a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+        a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1
+    )
+}
+
+/// A point at `ratio` of the line, moved randomly along the normal by up to 30% of the distance.
+fn control_point<R: RngCore + ?Sized>(from: (f64, f64), to: (f64, f64), ratio: f64, distance: f64, rng: &mut R) -> (f64, f64) {
+    let (x, y) = lerp(from, to, ratio);
+    if distance == 0.0 {
+        return (x, y);
+    }
+
+    let offset = rng.gen_range(-0.3..=0.3) * distance;
+    let normal = (-(to.1 - from.1) / distance, (to.0 - from.0) / distance);
+    (x + normal.0 * offset, y + normal.1 * offset)
+}
+
+/// A point past the target by 5% to 15% of the distance, at least 5 pixels.
+fn overshoot_point<R: RngCore + ?Sized>(from: (f64, f64), to: (f64, f64), distance: f64, rng: &mut R) -> (f64, f64) {
+    if distance == 0.0 {
+        return to;
+    }
+
+    let over = (rng.gen_range(0.05..=0.15) * distance).max(5.0);
+    let side = rng.gen_range(-0.5..=0.5) * over;
+    let dir = ((to.0 - from.0) / distance, (to.1 - from.1) / distance);
+    (to.0 + dir.0 * over - dir.1 * side, to.1 + dir.1 * over + dir.0 * side)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    use crate::trajectory::Trajectory;
+    use crate::trajectory::TrajectoryProfile;
+    use crate::types::Point;
+
+    #[test]
+    fn test_linear() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let points = Trajectory::new(TrajectoryProfile::Linear).generate(Point::new(0, 0), Point::new(100, 0), 500, &mut rng);
+        assert_eq!(points.len(), 5);
+        for (i, point) in points.iter().enumerate() {
+            assert_eq!(point.get_point(), Point::new(20 * (i as i32 + 1), 0));
+            assert_eq!(point.get_time(), 100 * (i as u64 + 1));
+        }
+    }
+
+    #[test]
+    fn test_ease_in_out() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let points = Trajectory::new(TrajectoryProfile::EaseInOut).step(10).generate(Point::new(0, 0), Point::new(0, 100), 1000, &mut rng);
+        assert_eq!(points.len(), 10);
+        assert_eq!(points[4].get_point(), Point::new(0, 50));
+        assert!(points[0].get_point().get_y() < 10);
+        assert!(points[8].get_point().get_y() > 90);
+        assert_eq!(points[9].get_point(), Point::new(0, 100));
+    }
+
+    #[test]
+    fn test_seeded_bezier() {
+        let trajectory = Trajectory::new(TrajectoryProfile::Bezier).jitter(2);
+        let from = Point::new(10, 10);
+        let to = Point::new(600, 400);
+        let first = trajectory.generate(from, to, 800, &mut SmallRng::seed_from_u64(7));
+        let second = trajectory.generate(from, to, 800, &mut SmallRng::seed_from_u64(7));
+        assert_eq!(first, second);
+        assert_eq!(first.last().unwrap().get_point(), to);
+        assert_eq!(first.last().unwrap().get_time(), 800);
+        assert!(first.windows(2).all(|w| w[0].get_time() <= w[1].get_time()));
+    }
+
+    #[test]
+    fn test_overshoot() {
+        let mut rng = SmallRng::seed_from_u64(3);
+        let points = Trajectory::new(TrajectoryProfile::Overshoot).generate(Point::new(0, 0), Point::new(200, 0), 500, &mut rng);
+        assert!(points.iter().any(|p| p.get_point().get_x() > 200));
+        assert_eq!(points.last().unwrap().get_point(), Point::new(200, 0));
+    }
+
+    #[test]
+    fn test_zero_distance() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let points = Trajectory::new(TrajectoryProfile::Bezier).generate(Point::new(5, 5), Point::new(5, 5), 300, &mut rng);
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].get_point(), Point::new(5, 5));
+    }
+}
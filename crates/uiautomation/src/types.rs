@@ -37,6 +37,16 @@ impl Point {
     pub fn set_y(&mut self, y: i32) {
         self.0.y = y;
     }
+
+    /// Converts the point from physical pixels to logical pixels of the `scale` factor, such as `1.5` for 150% scaling.
+    pub fn to_logical(&self, scale: f64) -> Point {
+        Point::new(scale_value(self.0.x, 1.0 / valid_scale(scale)), scale_value(self.0.y, 1.0 / valid_scale(scale)))
+    }
+
+    /// Converts the point from logical pixels of the `scale` factor to physical pixels.
+    pub fn to_physical(&self, scale: f64) -> Point {
+        Point::new(scale_value(self.0.x, valid_scale(scale)), scale_value(self.0.y, valid_scale(scale)))
+    }
 }
 
 impl Debug for Point {
@@ -145,6 +155,35 @@ impl Rect {
     pub fn get_right_bottom(&self) -> Point {
         Point::new(self.get_right(), self.get_bottom())
     }
+
+    /// Retrieves the width of the rect.
+    pub fn get_width(&self) -> i32 {
+        self.0.right - self.0.left
+    }
+
+    /// Retrieves the height of the rect.
+    pub fn get_height(&self) -> i32 {
+        self.0.bottom - self.0.top
+    }
+
+    /// Determines whether the `point` is in the rect. The right and bottom edges are excluded.
+    pub fn contains(&self, point: Point) -> bool {
+        point.get_x() >= self.0.left && point.get_x() < self.0.right && point.get_y() >= self.0.top && point.get_y() < self.0.bottom
+    }
+
+    /// Converts the rect from physical pixels to logical pixels of the `scale` factor.
+    /// 
+    /// Each edge is rounded separately, so adjacent rects still share their edges after conversion.
+    pub fn to_logical(&self, scale: f64) -> Rect {
+        let factor = 1.0 / valid_scale(scale);
+        Rect::new(scale_value(self.0.left, factor), scale_value(self.0.top, factor), scale_value(self.0.right, factor), scale_value(self.0.bottom, factor))
+    }
+
+    /// Converts the rect from logical pixels of the `scale` factor to physical pixels.
+    pub fn to_physical(&self, scale: f64) -> Rect {
+        let factor = valid_scale(scale);
+        Rect::new(scale_value(self.0.left, factor), scale_value(self.0.top, factor), scale_value(self.0.right, factor), scale_value(self.0.bottom, factor))
+    }
 }
 
 impl Debug for Rect {
@@ -189,6 +228,18 @@ impl AsMut<RECT> for Rect {
 //     }
 // }
 
+fn valid_scale(scale: f64) -> f64 {
+    if scale.is_finite() && scale > 0.0 {
+        scale
+    } else {
+        1.0
+    }
+}
+
+fn scale_value(value: i32, factor: f64) -> i32 {
+    (value as f64 * factor).round() as i32
+}
+
 /// A Wrapper for windows `HWND`.
 #[derive(Default, Clone, Copy)]
 pub struct Handle(HWND);
@@ -227,4 +278,27 @@ impl AsRef<HWND> for Handle {
 //     fn into_param(self) -> Param<'a, HWND> {
 //         Param::Owned(self.0)
 //     }
-// }
\ No newline at end of file
+// }
+
+#[cfg(test)]
+mod tests {
+    use crate::types::Point;
+    use crate::types::Rect;
+
+    #[test]
+    fn test_point_scale() {
+        let point = Point::new(300, -151);
+        assert_eq!(point.to_logical(1.5), Point::new(200, -101));
+        assert_eq!(Point::new(200, -101).to_physical(1.5), Point::new(300, -152));
+        assert_eq!(point.to_logical(0.0), point);
+    }
+
+    #[test]
+    fn test_rect_scale() {
+        let left = Rect::new(0, 0, 1001, 500).to_logical(1.25);
+        let right = Rect::new(1001, 0, 2000, 500).to_logical(1.25);
+        assert_eq!(left, Rect::new(0, 0, 801, 400));
+        assert_eq!(left.get_right(), right.get_left());
+        assert_eq!(right.to_physical(1.25), Rect::new(1001, 0, 2000, 500));
+    }
+}
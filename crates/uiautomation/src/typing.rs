@@ -0,0 +1,225 @@
+use rand::Rng;
+use rand::RngCore;
+
+use super::inputs::InputEvent;
+
+/// An input event and the delay before sending it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduledEvent {
+    event: InputEvent,
+    delay: u64
+}
+
+impl ScheduledEvent {
+    /// Creates a scheduled event.
+    pub fn new(event: InputEvent, delay: u64) -> Self {
+        Self {
+            event,
+            delay
+        }
+    }
+
+    /// Retrieves the input event.
+    pub fn get_event(&self) -> InputEvent {
+        self.event
+    }
+
+    /// Retrieves the delay in milliseconds before sending the event.
+    pub fn get_delay(&self) -> u64 {
+        self.delay
+    }
+}
+
+/// The input events of a keystroke and the character which it types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Stroke {
+    pub(crate) events: Vec<InputEvent>,
+    pub(crate) ch: Option<char>
+}
+
+impl Stroke {
+    pub(crate) fn new(events: Vec<InputEvent>, ch: Option<char>) -> Self {
+        Self {
+            events,
+            ch
+        }
+    }
+}
+
+/// The cadence of typing keys, such as the delay between keys and the time to hold a key.
+/// 
+/// The delays are chosen randomly from their ranges, so the typing looks like a human.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use uiautomation::inputs::Keyboard;
+/// use uiautomation::inputs::RecordingSink;
+/// use uiautomation::typing::TypingProfile;
+/// 
+/// let profile = TypingProfile::new().delay(50, 120).hold(20, 40).whitespace_pause(200);
+/// let keyboard = Keyboard::with_sink(RecordingSink::new()).profile(profile).seed(1);
+/// let schedule = keyboard.schedule_keys("Hi there").unwrap();
+/// assert_eq!(schedule.len(), 16);
+/// assert!(schedule[1].get_delay() >= 20 && schedule[1].get_delay() <= 40);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypingProfile {
+    delay: (u64, u64),
+    hold: (u64, u64),
+    whitespace_pause: u64,
+    punctuation_pause: u64
+}
+
+impl Default for TypingProfile {
+    fn default() -> Self {
+        Self {
+            delay: (40, 120),
+            hold: (20, 60),
+            whitespace_pause: 0,
+            punctuation_pause: 0
+        }
+    }
+}
+
+impl TypingProfile {
+    /// Creates a typing profile with a `40..=120` millisecond delay between keys and a `20..=60` millisecond hold time.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the range of the delay in milliseconds between two keys.
+    pub fn delay(mut self, min: u64, max: u64) -> Self {
+        self.delay = ordered(min, max);
+        self
+    }
+
+    /// Sets the range of the time in milliseconds to hold a key down.
+    pub fn hold(mut self, min: u64, max: u64) -> Self {
+        self.hold = ordered(min, max);
+        self
+    }
+
+    /// Sets the extra pause in milliseconds after typing a whitespace character. `0` is default value.
+    pub fn whitespace_pause(mut self, pause: u64) -> Self {
+        self.whitespace_pause = pause;
+        self
+    }
+
+    /// Sets the extra pause in milliseconds after typing an ASCII punctuation character. `0` is default value.
+    pub fn punctuation_pause(mut self, pause: u64) -> Self {
+        self.punctuation_pause = pause;
+        self
+    }
+
+    /// Schedules the events of the `strokes`.
+    /// 
+    /// A released key waits for the hold time, a pressed key after a released one waits for the delay between keys,
+    /// and the modifiers around a key are sent without delay.
+    pub(crate) fn schedule<R: RngCore + ?Sized>(&self, strokes: &[Stroke], rng: &mut R) -> Vec<ScheduledEvent> {
+        let mut schedule: Vec<ScheduledEvent> = Vec::new();
+        let mut pause = 0;
+        for stroke in strokes {
+            let mut prev_down: Option<bool> = None;
+            for (i, event) in stroke.events.iter().enumerate() {
+                let down = is_down(event);
+                let delay = if i == 0 {
+                    if schedule.is_empty() {
+                        0
+                    } else {
+                        rng.gen_range(self.delay.0..=self.delay.1) + pause
+                    }
+                } else {
+                    match (prev_down, down) {
+                        (Some(true), false) => rng.gen_range(self.hold.0..=self.hold.1),
+                        (Some(false), true) => rng.gen_range(self.delay.0..=self.delay.1),
+                        _ => 0
+                    }
+                };
+                schedule.push(ScheduledEvent::new(*event, delay));
+                prev_down = Some(down);
+            }
+
+            if !stroke.events.is_empty() {
+                pause = match stroke.ch {
+                    Some(ch) if ch.is_whitespace() => self.whitespace_pause,
+                    Some(ch) if ch.is_ascii_punctuation() => self.punctuation_pause,
+                    _ => 0
+                };
+            }
+        }
+
+        schedule
+    }
+}
+
+/// Schedules the events of the `strokes` with a fixed `interval` in milliseconds between each two events.
+pub(crate) fn schedule_interval(strokes: &[Stroke], interval: u64) -> Vec<ScheduledEvent> {
+    strokes.iter().flat_map(|s| s.events.iter()).enumerate().map(|(i, event)| {
+        ScheduledEvent::new(*event, if i == 0 { 0 } else { interval })
+    }).collect()
+}
+
+fn ordered(min: u64, max: u64) -> (u64, u64) {
+    if min <= max {
+        (min, max)
+    } else {
+        (max, min)
+    }
+}
+
+fn is_down(event: &InputEvent) -> bool {
+    matches!(event, InputEvent::KeyDown(_) | InputEvent::UnicodeDown(_) | InputEvent::ButtonDown(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+    use crate::inputs::InputEvent;
+    use crate::inputs::KeyCode;
+    use crate::typing::Stroke;
+    use crate::typing::TypingProfile;
+    use crate::typing::schedule_interval;
+
+    fn char_stroke(ch: char) -> Stroke {
+        Stroke::new(vec![InputEvent::UnicodeDown(ch as u16), InputEvent::UnicodeUp(ch as u16)], Some(ch))
+    }
+
+    #[test]
+    fn test_schedule() {
+        let shift_a = Stroke::new(vec![
+            InputEvent::KeyDown(KeyCode::Virtual(VK_SHIFT)),
+            InputEvent::KeyDown(KeyCode::Virtual(VK_A)),
+            InputEvent::KeyUp(KeyCode::Virtual(VK_A)),
+            InputEvent::KeyUp(KeyCode::Virtual(VK_SHIFT))
+        ], Some('A'));
+        let strokes = vec![char_stroke('a'), char_stroke(' '), shift_a, char_stroke(','), char_stroke('b')];
+
+        let profile = TypingProfile::new().delay(10, 20).hold(1, 5).whitespace_pause(100).punctuation_pause(300);
+        let schedule = profile.schedule(&strokes, &mut SmallRng::seed_from_u64(5));
+        let delays: Vec<u64> = schedule.iter().map(|e| e.get_delay()).collect();
+        assert_eq!(delays.len(), 12);
+        assert_eq!(delays[0], 0);
+        assert!((1..=5).contains(&delays[1]));
+        assert!((10..=20).contains(&delays[2]));
+        assert!((110..=120).contains(&delays[4]));
+        assert_eq!(delays[5], 0);
+        assert!((1..=5).contains(&delays[6]));
+        assert_eq!(delays[7], 0);
+        assert!((10..=20).contains(&delays[8]));
+        assert!((310..=320).contains(&delays[10]));
+
+        let again = profile.schedule(&strokes, &mut SmallRng::seed_from_u64(5));
+        assert_eq!(schedule, again);
+    }
+
+    #[test]
+    fn test_schedule_interval() {
+        let schedule = schedule_interval(&[char_stroke('a'), char_stroke('b')], 30);
+        let delays: Vec<u64> = schedule.iter().map(|e| e.get_delay()).collect();
+        assert_eq!(delays, vec![0, 30, 30, 30]);
+    }
+}